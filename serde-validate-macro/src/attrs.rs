@@ -0,0 +1,596 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Parsing of `#[validate(...)]` attributes.
+
+use crate::bound::parse_bound;
+use proc_macro2::Ident;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    bracketed, parenthesized, parse_quote, Attribute, Expr, ExprPath, LitStr, Member, Path, Type,
+    WherePredicate,
+};
+
+/// The paths of the crates the generated code refers to.
+///
+/// They can be overridden when the macros are used through a crate re-exporting this one.
+#[derive(Default)]
+pub(crate) struct CratePaths {
+    /// The path of `serde_validate`, from `crate = "..."`.
+    krate: Option<Path>,
+    /// The path of `serde`, from `serde = "..."`.
+    serde: Option<Path>,
+}
+
+impl CratePaths {
+    /// Parses `crate = ...` or `serde = ...`, returning `false` for any other argument.
+    pub(crate) fn parse(&mut self, meta: &ParseNestedMeta) -> syn::Result<bool> {
+        if meta.path.is_ident("crate") {
+            self.krate = Some(parse_mod_path(meta)?);
+            Ok(true)
+        } else if meta.path.is_ident("serde") {
+            self.serde = Some(parse_mod_path(meta)?);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the path of `serde_validate`, `::serde_validate` by default.
+    pub(crate) fn krate(&self) -> Path {
+        self.krate
+            .clone()
+            .unwrap_or_else(|| parse_quote!(::serde_validate))
+    }
+
+    /// Returns the path of `serde`, the one re-exported by `serde_validate` by default.
+    pub(crate) fn serde(&self) -> Path {
+        self.serde.clone().unwrap_or_else(|| {
+            let krate = self.krate();
+            parse_quote!(#krate::serde)
+        })
+    }
+}
+
+/// The arguments of `#[validate_deser(...)]`.
+#[derive(Default)]
+pub(crate) struct DeserArgs {
+    /// The context type of `context = Ctx`, validating through `ValidateWith<Ctx>`.
+    pub(crate) context: Option<Type>,
+    /// The predicates of `bound = "..."`, replacing the inferred ones.
+    pub(crate) bound: Option<Punctuated<WherePredicate, Comma>>,
+    /// The function of `map_err = path::to_fn`, converting the validation error.
+    pub(crate) map_err: Option<ExprPath>,
+    /// The group of `group = "..."`, validating through `Validate::validate_group`.
+    pub(crate) group: Option<LitStr>,
+    pub(crate) paths: CratePaths,
+}
+
+impl DeserArgs {
+    pub(crate) fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if self.paths.parse(&meta)? {
+            Ok(())
+        } else if meta.path.is_ident("context") {
+            self.context = Some(parse_type(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("map_err") {
+            self.map_err = Some(parse_path(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("group") {
+            self.group = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("bound") {
+            self.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+            Ok(())
+        } else {
+            Err(meta.error("unknown `validate_deser` argument"))
+        }
+    }
+}
+
+/// A rule declared on a field or a container.
+pub(crate) enum Rule {
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Length {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Custom(ExprPath),
+    /// An `Option` field that must be set.
+    Required,
+    /// A field of the struct that must be set, from `required_if` and `required_unless`.
+    RequiredField(Member),
+    /// Fields of the struct of which exactly one must be set.
+    ExactlyOneOf(Vec<Member>),
+    /// A custom rule that `validate_deser` also checks as soon as the field is deserialized.
+    Field(ExprPath),
+    /// A maximum number of elements that `validate_deser` also enforces while deserializing.
+    MaxItems(Expr),
+    Nested,
+    /// The warnings of a `nested` field, reported among the warnings of the container.
+    NestedWarnings,
+    CustomAsync(ExprPath),
+    NestedAsync,
+    /// A rule of an attribute with `groups = [...]`, only checked for one of the groups.
+    Grouped {
+        groups: Vec<LitStr>,
+        rule: Box<Rule>,
+    },
+    /// A rule only checked when `condition` holds, or when it does not if `unless` is set.
+    When {
+        condition: ExprPath,
+        unless: bool,
+        rule: Box<Rule>,
+    },
+}
+
+impl Rule {
+    /// Returns `true` if the rule needs to be awaited, so it only applies to `AsyncValidate`.
+    pub(crate) fn is_async(&self) -> bool {
+        match self {
+            Rule::CustomAsync(_) | Rule::NestedAsync => true,
+            Rule::Grouped { rule, .. } | Rule::When { rule, .. } => rule.is_async(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the rule is only checked for some groups.
+    pub(crate) fn is_grouped(&self) -> bool {
+        matches!(self, Rule::Grouped { .. })
+    }
+
+    /// Returns `true` if the rule depends on a condition on the whole instance.
+    pub(crate) fn is_conditional(&self) -> bool {
+        match self {
+            Rule::When { .. } => true,
+            Rule::Grouped { rule, .. } => rule.is_conditional(),
+            _ => false,
+        }
+    }
+
+    /// Returns the rule checked once its groups and conditions are met.
+    pub(crate) fn inner(&self) -> &Rule {
+        match self {
+            Rule::Grouped { rule, .. } | Rule::When { rule, .. } => rule.inner(),
+            rule => rule,
+        }
+    }
+
+    /// Returns the fields of the struct that the rule refers to.
+    pub(crate) fn fields(&self) -> &[Member] {
+        match self.inner() {
+            Rule::RequiredField(field) => std::slice::from_ref(field),
+            Rule::ExactlyOneOf(fields) => fields,
+            _ => &[],
+        }
+    }
+}
+
+/// How `validate_deser` repairs a field breaking its rules, from `on_invalid = "..."`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OnInvalid {
+    /// Replaces the value by its default.
+    Default,
+    /// Drops the invalid elements of a collection.
+    Skip,
+    /// Clamps the value into the bounds of its `range` rule.
+    Clamp,
+}
+
+/// The parsed `#[validate(...)]` attributes of a field.
+pub(crate) struct FieldAttrs {
+    pub(crate) rules: Vec<Rule>,
+    /// The rules of the attributes marked `warn`, whose failures are only warnings.
+    pub(crate) warnings: Vec<Rule>,
+    pub(crate) on_invalid: Option<OnInvalid>,
+}
+
+/// The parsed `#[validate(...)]` attributes of a struct or enum.
+pub(crate) struct ContainerAttrs {
+    pub(crate) rules: Vec<Rule>,
+    /// The rules of the attributes marked `warn`, whose failures are only warnings.
+    pub(crate) warnings: Vec<Rule>,
+    /// The crate paths, of which only `crate = "..."` is accepted.
+    pub(crate) paths: CratePaths,
+}
+
+pub(crate) fn is_validate_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("validate")
+}
+
+impl FieldAttrs {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut rules = Vec::new();
+        let mut warnings = Vec::new();
+        let mut on_invalid = None;
+        let mut on_invalid_span = None;
+        for attr in attrs.iter().filter(|attr| is_validate_attr(attr)) {
+            let mut attr_rules = Vec::new();
+            let mut warn = false;
+            let mut groups = None;
+            let mut when = None;
+            attr.parse_nested_meta(|meta| {
+                let rules = &mut attr_rules;
+                if meta.path.is_ident("warn") {
+                    warn = true;
+                    Ok(())
+                } else if meta.path.is_ident("groups") {
+                    groups = Some(parse_groups(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("when") {
+                    when = Some(parse_path(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("on_invalid") {
+                    let policy: LitStr = meta.value()?.parse()?;
+                    on_invalid = Some(parse_on_invalid(&policy)?);
+                    on_invalid_span = Some(policy.span());
+                    Ok(())
+                } else if meta.path.is_ident("range") {
+                    let (min, max) = parse_bounds(&meta, "range")?;
+                    rules.push(Rule::Range { min, max });
+                    Ok(())
+                } else if meta.path.is_ident("length") {
+                    let (min, max) = parse_bounds(&meta, "length")?;
+                    rules.push(Rule::Length { min, max });
+                    Ok(())
+                } else if meta.path.is_ident("custom") {
+                    rules.push(Rule::Custom(parse_path(&meta)?));
+                    Ok(())
+                } else if meta.path.is_ident("required") {
+                    rules.push(Rule::Required);
+                    Ok(())
+                } else if meta.path.is_ident("field") {
+                    rules.push(Rule::Field(parse_path(&meta)?));
+                    Ok(())
+                } else if meta.path.is_ident("max_items") {
+                    rules.push(Rule::MaxItems(meta.value()?.parse()?));
+                    Ok(())
+                } else if meta.path.is_ident("nested") {
+                    rules.push(Rule::Nested);
+                    Ok(())
+                } else if meta.path.is_ident("custom_async") {
+                    rules.push(Rule::CustomAsync(parse_path(&meta)?));
+                    Ok(())
+                } else if meta.path.is_ident("nested_async") {
+                    rules.push(Rule::NestedAsync);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown validation rule"))
+                }
+            })?;
+            if attr_rules.iter().any(|rule| matches!(rule, Rule::Nested)) {
+                warnings.extend(condition_rules(vec![Rule::NestedWarnings], when.as_ref()));
+            }
+            let attr_rules = condition_rules(attr_rules, when.as_ref());
+            let attr_rules = group_rules(attr, attr_rules, groups, warn)?;
+            if warn {
+                warnings.extend(attr_rules);
+            } else {
+                rules.extend(attr_rules);
+            }
+        }
+        if let Some(span) = on_invalid_span {
+            if on_invalid == Some(OnInvalid::Clamp)
+                && !rules.iter().any(|rule| matches!(rule, Rule::Range { .. }))
+            {
+                return Err(syn::Error::new(
+                    span,
+                    "`on_invalid = \"clamp\"` needs a `range` rule",
+                ));
+            }
+        }
+        Ok(FieldAttrs {
+            rules,
+            warnings,
+            on_invalid,
+        })
+    }
+}
+
+impl ContainerAttrs {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut rules = Vec::new();
+        let mut warnings = Vec::new();
+        let mut paths = CratePaths::default();
+        for attr in attrs.iter().filter(|attr| is_validate_attr(attr)) {
+            let mut attr_rules = Vec::new();
+            let mut warn = false;
+            let mut groups = None;
+            let mut when = None;
+            attr.parse_nested_meta(|meta| {
+                let rules = &mut attr_rules;
+                if meta.path.is_ident("warn") {
+                    warn = true;
+                    Ok(())
+                } else if meta.path.is_ident("groups") {
+                    groups = Some(parse_groups(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("when") {
+                    when = Some(parse_path(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    paths.parse(&meta)?;
+                    Ok(())
+                } else if meta.path.is_ident("custom") {
+                    rules.push(Rule::Custom(parse_path(&meta)?));
+                    Ok(())
+                } else if meta.path.is_ident("custom_async") {
+                    rules.push(Rule::CustomAsync(parse_path(&meta)?));
+                    Ok(())
+                } else if meta.path.is_ident("required_if") {
+                    rules.push(parse_required_if(&meta, "required_if", false)?);
+                    Ok(())
+                } else if meta.path.is_ident("required_unless") {
+                    rules.push(parse_required_if(&meta, "required_unless", true)?);
+                    Ok(())
+                } else if meta.path.is_ident("exactly_one_of") {
+                    rules.push(parse_exactly_one_of(&meta)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown container validation rule"))
+                }
+            })?;
+            let attr_rules = condition_rules(attr_rules, when.as_ref());
+            let attr_rules = group_rules(attr, attr_rules, groups, warn)?;
+            if warn {
+                warnings.extend(attr_rules);
+            } else {
+                rules.extend(attr_rules);
+            }
+        }
+        Ok(ContainerAttrs {
+            rules,
+            warnings,
+            paths,
+        })
+    }
+}
+
+/// A sanitizer declared on a field.
+pub(crate) enum Sanitizer {
+    /// A function of `serde_validate::sanitizers`, named like the attribute.
+    Builtin(Ident),
+    Custom(ExprPath),
+    Nested,
+}
+
+/// The sanitizers of `serde_validate::sanitizers`.
+const BUILTIN_SANITIZERS: [&str; 5] = [
+    "trim",
+    "lowercase",
+    "uppercase",
+    "collapse_whitespace",
+    "nfc",
+];
+
+/// The parsed `#[sanitizer(...)]` attributes of a field.
+pub(crate) struct SanitizeAttrs {
+    pub(crate) sanitizers: Vec<Sanitizer>,
+}
+
+pub(crate) fn is_sanitize_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("sanitizer")
+}
+
+impl SanitizeAttrs {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut sanitizers = Vec::new();
+        for attr in attrs.iter().filter(|attr| is_sanitize_attr(attr)) {
+            attr.parse_nested_meta(|meta| {
+                match meta.path.get_ident() {
+                    Some(ident) if BUILTIN_SANITIZERS.iter().any(|name| ident == name) => {
+                        sanitizers.push(Sanitizer::Builtin(ident.clone()));
+                    }
+                    Some(ident) if ident == "custom" => {
+                        sanitizers.push(Sanitizer::Custom(parse_path(&meta)?));
+                    }
+                    Some(ident) if ident == "nested" => sanitizers.push(Sanitizer::Nested),
+                    _ => return Err(meta.error("unknown sanitizer")),
+                }
+                Ok(())
+            })?;
+        }
+        Ok(SanitizeAttrs { sanitizers })
+    }
+
+    /// Parses the `#[sanitizer(...)]` attributes of a struct or enum, which only accept
+    /// `crate = "..."`.
+    pub(crate) fn container_paths(attrs: &[Attribute]) -> syn::Result<CratePaths> {
+        let mut paths = CratePaths::default();
+        for attr in attrs.iter().filter(|attr| is_sanitize_attr(attr)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    paths.parse(&meta)?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown container sanitize attribute"))
+                }
+            })?;
+        }
+        Ok(paths)
+    }
+}
+
+/// Parses `name(min = ..., max = ...)`, where both bounds are optional but at least one is given.
+fn parse_bounds(meta: &ParseNestedMeta, rule: &str) -> syn::Result<(Option<Expr>, Option<Expr>)> {
+    let mut min = None;
+    let mut max = None;
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("min") {
+            min = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("max") {
+            max = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error(format!(
+                "unknown `{rule}` parameter, expected `min` or `max`"
+            )))
+        }
+    })?;
+    if min.is_none() && max.is_none() {
+        return Err(meta.error(format!("`{rule}` needs at least one of `min` or `max`")));
+    }
+    Ok((min, max))
+}
+
+/// Parses `groups = ["name", ...]`.
+fn parse_groups(meta: &ParseNestedMeta) -> syn::Result<Vec<LitStr>> {
+    let value = meta.value()?;
+    let content;
+    bracketed!(content in value);
+    let groups = Punctuated::<LitStr, Comma>::parse_terminated(&content)?;
+    if groups.is_empty() {
+        return Err(meta.error("`groups` needs at least one group"));
+    }
+    Ok(groups.into_iter().collect())
+}
+
+/// Parses `name(field = ..., when = path::to_fn)`, where `field` must be set when the function
+/// returns `true`, or when it returns `false` if `unless` is set.
+fn parse_required_if(meta: &ParseNestedMeta, rule: &str, unless: bool) -> syn::Result<Rule> {
+    let mut field = None;
+    let mut condition = None;
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("field") {
+            field = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("when") {
+            condition = Some(parse_path(&meta)?);
+            Ok(())
+        } else {
+            Err(meta.error(format!(
+                "unknown `{rule}` parameter, expected `field` or `when`"
+            )))
+        }
+    })?;
+    let (Some(field), Some(condition)) = (field, condition) else {
+        return Err(meta.error(format!("`{rule}` needs both `field` and `when`")));
+    };
+    Ok(Rule::When {
+        condition,
+        unless,
+        rule: Box::new(Rule::RequiredField(field)),
+    })
+}
+
+/// Parses `exactly_one_of(field, ...)`, naming at least two fields.
+fn parse_exactly_one_of(meta: &ParseNestedMeta) -> syn::Result<Rule> {
+    let content;
+    parenthesized!(content in meta.input);
+    let fields = Punctuated::<Member, Comma>::parse_terminated(&content)?;
+    if fields.len() < 2 {
+        return Err(meta.error("`exactly_one_of` needs at least two fields"));
+    }
+    Ok(Rule::ExactlyOneOf(fields.into_iter().collect()))
+}
+
+/// Restricts the rules of an attribute to its `when = path::to_fn` condition, if any.
+fn condition_rules(rules: Vec<Rule>, when: Option<&ExprPath>) -> Vec<Rule> {
+    let Some(condition) = when else {
+        return rules;
+    };
+    rules
+        .into_iter()
+        .map(|rule| Rule::When {
+            condition: condition.clone(),
+            unless: false,
+            rule: Box::new(rule),
+        })
+        .collect()
+}
+
+/// Restricts the rules of `attr` to its `groups = [...]`, if any.
+fn group_rules(
+    attr: &Attribute,
+    rules: Vec<Rule>,
+    groups: Option<Vec<LitStr>>,
+    warn: bool,
+) -> syn::Result<Vec<Rule>> {
+    let Some(groups) = groups else {
+        return Ok(rules);
+    };
+    if warn {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "`warn` rules cannot be restricted to `groups`",
+        ));
+    }
+    if rules.iter().any(Rule::is_async) {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "asynchronous rules cannot be restricted to `groups`",
+        ));
+    }
+    Ok(rules
+        .into_iter()
+        .map(|rule| Rule::Grouped {
+            groups: groups.clone(),
+            rule: Box::new(rule),
+        })
+        .collect())
+}
+
+/// Parses the policy of `on_invalid = "..."`.
+fn parse_on_invalid(policy: &LitStr) -> syn::Result<OnInvalid> {
+    match policy.value().as_str() {
+        "default" => Ok(OnInvalid::Default),
+        "skip" => Ok(OnInvalid::Skip),
+        "clamp" => Ok(OnInvalid::Clamp),
+        _ => Err(syn::Error::new(
+            policy.span(),
+            "unknown `on_invalid` policy, expected `default`, `skip` or `clamp`",
+        )),
+    }
+}
+
+/// Parses `name = path::to_fn` or `name = "path::to_fn"`.
+fn parse_path(meta: &ParseNestedMeta) -> syn::Result<ExprPath> {
+    let value = meta.value()?;
+    if value.peek(LitStr) {
+        value.parse::<LitStr>()?.parse()
+    } else {
+        value.parse()
+    }
+}
+
+/// Parses `name = path::to_mod` or `name = "path::to_mod"`.
+fn parse_mod_path(meta: &ParseNestedMeta) -> syn::Result<Path> {
+    let value = meta.value()?;
+    if value.peek(LitStr) {
+        value.parse::<LitStr>()?.parse_with(Path::parse_mod_style)
+    } else {
+        value.call(Path::parse_mod_style)
+    }
+}
+
+/// Parses `name = Type` or `name = "Type"`.
+fn parse_type(meta: &ParseNestedMeta) -> syn::Result<Type> {
+    let value = meta.value()?;
+    if value.peek(LitStr) {
+        value.parse::<LitStr>()?.parse()
+    } else {
+        value.parse()
+    }
+}
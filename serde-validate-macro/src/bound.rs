@@ -0,0 +1,295 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Inference of the bounds of the generated serde impls.
+//!
+//! The generated impls must require what the derived impls of the helper types require, so the
+//! bounds follow serde's own inference: a type is bounded only if it appears in a field that serde
+//! handles itself, outside of `PhantomData`.
+
+use crate::serde_attrs::serde_metas;
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    Data, DeriveInput, Expr, ExprLit, GenericArgument, Lit, LitStr, Meta, Path, PathArguments,
+    ReturnType, Type, TypeParamBound, WherePredicate,
+};
+
+/// The serde trait a generated impl implements.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Serialize,
+    Deserialize,
+}
+
+impl Direction {
+    fn name(self) -> &'static str {
+        match self {
+            Direction::Serialize => "serialize",
+            Direction::Deserialize => "deserialize",
+        }
+    }
+
+    /// Returns `true` if serde handles the field or variant with these attributes itself, rather
+    /// than skipping it, delegating it to a function or using an explicit bound.
+    fn handles(self, metas: &[Meta]) -> bool {
+        let name = self.name();
+        let skip = format!("skip_{name}");
+        let with = format!("{name}_with");
+        !metas.iter().any(|meta| {
+            let path = meta.path();
+            path.is_ident("skip")
+                || path.is_ident(&skip)
+                || path.is_ident("with")
+                || path.is_ident(&with)
+                || path.is_ident("bound")
+        })
+    }
+
+    /// Returns `true` if the field with these attributes is deserialized from `Default`.
+    fn requires_default(self, metas: &[Meta]) -> bool {
+        if self == Direction::Serialize {
+            return false;
+        }
+        let skipped = metas
+            .iter()
+            .any(|meta| meta.path().is_ident("skip") || meta.path().is_ident("skip_deserializing"));
+        let default = metas.iter().find(|meta| meta.path().is_ident("default"));
+        match default {
+            Some(Meta::Path(_)) => true,
+            Some(_) => false,
+            None => skipped,
+        }
+    }
+}
+
+/// Returns the predicates of the `direction` impl of `input`, where `bound` is the serde trait.
+///
+/// A container `#[serde(bound = "...")]` replaces the inferred predicates, as it does for serde.
+pub(crate) fn predicates(
+    input: &DeriveInput,
+    direction: Direction,
+    bound: &TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    if let Some(predicates) = explicit_bound(&serde_metas(&input.attrs)?, direction)? {
+        return Ok(predicates.iter().map(ToTokens::to_token_stream).collect());
+    }
+
+    let params: Vec<&Ident> = input.generics.type_params().map(|p| &p.ident).collect();
+    let mut bounded = Vec::new();
+    let mut defaulted = Vec::new();
+    let mut predicates = Vec::new();
+    let mut variant_predicates = Vec::new();
+
+    let mut visit_fields = |fields: &syn::Fields, variant_handled: bool| -> syn::Result<()> {
+        for field in fields {
+            let metas = serde_metas(&field.attrs)?;
+            if let Some(explicit) = explicit_bound(&metas, direction)? {
+                predicates.extend(explicit.iter().map(ToTokens::to_token_stream));
+            }
+            if variant_handled && direction.handles(&metas) {
+                visit_type(&field.ty, &params, &mut bounded);
+            }
+            if direction.requires_default(&metas) {
+                visit_type(&field.ty, &params, &mut defaulted);
+            }
+        }
+        Ok(())
+    };
+
+    match input.data {
+        Data::Struct(ref data) => visit_fields(&data.fields, true)?,
+        Data::Enum(ref data) => {
+            for variant in &data.variants {
+                let metas = serde_metas(&variant.attrs)?;
+                if let Some(explicit) = explicit_bound(&metas, direction)? {
+                    variant_predicates.extend(explicit.iter().map(ToTokens::to_token_stream));
+                }
+                visit_fields(&variant.fields, direction.handles(&metas))?;
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    predicates.extend(variant_predicates);
+
+    predicates.extend(bounded.iter().map(|ty| quote! { #ty: #bound }));
+    predicates.extend(
+        defaulted
+            .iter()
+            .map(|ty| quote! { #ty: ::core::default::Default }),
+    );
+    Ok(predicates)
+}
+
+/// Parses `bound = "..."` or `bound(serialize = "...", deserialize = "...")` for `direction`.
+fn explicit_bound(
+    metas: &[Meta],
+    direction: Direction,
+) -> syn::Result<Option<Punctuated<WherePredicate, Comma>>> {
+    let Some(meta) = metas.iter().find(|meta| meta.path().is_ident("bound")) else {
+        return Ok(None);
+    };
+    match meta {
+        Meta::NameValue(meta) => parse_predicates(&meta.value).map(Some),
+        Meta::List(list) => {
+            let mut predicates = None;
+            list.parse_nested_meta(|meta| {
+                let value: Expr = meta.value()?.parse()?;
+                if meta.path.is_ident(direction.name()) {
+                    predicates = Some(parse_predicates(&value)?);
+                }
+                Ok(())
+            })?;
+            Ok(predicates)
+        }
+        Meta::Path(path) => Err(syn::Error::new_spanned(path, "expected `bound = \"...\"`")),
+    }
+}
+
+fn parse_predicates(value: &Expr) -> syn::Result<Punctuated<WherePredicate, Comma>> {
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => parse_bound(lit),
+        _ => Err(syn::Error::new_spanned(value, "expected a string literal")),
+    }
+}
+
+/// Parses the where predicates of a `bound = "..."` string.
+pub(crate) fn parse_bound(lit: &LitStr) -> syn::Result<Punctuated<WherePredicate, Comma>> {
+    lit.parse_with(Punctuated::parse_terminated)
+}
+
+/// Records in `found` the types of `ty` that need a bound: the type parameters in `params` and
+/// their associated types.
+fn visit_type(ty: &Type, params: &[&Ident], found: &mut Vec<Type>) {
+    match ty {
+        Type::Path(ty) => {
+            if let Some(ref qself) = ty.qself {
+                visit_type(&qself.ty, params, found);
+            }
+            visit_path(&ty.path, params, found);
+        }
+        Type::Reference(ty) => visit_type(&ty.elem, params, found),
+        Type::Slice(ty) => visit_type(&ty.elem, params, found),
+        Type::Array(ty) => visit_type(&ty.elem, params, found),
+        Type::Ptr(ty) => visit_type(&ty.elem, params, found),
+        Type::Paren(ty) => visit_type(&ty.elem, params, found),
+        Type::Group(ty) => visit_type(&ty.elem, params, found),
+        Type::Tuple(ty) => {
+            for elem in &ty.elems {
+                visit_type(elem, params, found);
+            }
+        }
+        Type::BareFn(ty) => {
+            for input in &ty.inputs {
+                visit_type(&input.ty, params, found);
+            }
+            if let ReturnType::Type(_, ref output) = ty.output {
+                visit_type(output, params, found);
+            }
+        }
+        Type::TraitObject(ty) => visit_bounds(&ty.bounds, params, found),
+        Type::ImplTrait(ty) => visit_bounds(&ty.bounds, params, found),
+        ty => visit_tokens(ty.to_token_stream(), params, found),
+    }
+}
+
+fn visit_path(path: &Path, params: &[&Ident], found: &mut Vec<Type>) {
+    if path.leading_colon.is_none() {
+        if let Some(param) = path
+            .segments
+            .first()
+            .and_then(|first| params.iter().copied().find(|param| first.ident == **param))
+        {
+            // `T` is bounded itself, `T::Assoc` only needs the associated type to be bounded.
+            let ty: Type = if path.segments.len() == 1 {
+                syn::parse_quote!(#param)
+            } else {
+                syn::parse_quote!(#path)
+            };
+            insert(found, ty);
+        }
+    }
+    for segment in &path.segments {
+        if segment.ident == "PhantomData" {
+            continue;
+        }
+        match segment.arguments {
+            PathArguments::AngleBracketed(ref args) => {
+                for arg in &args.args {
+                    match arg {
+                        GenericArgument::Type(ty) => visit_type(ty, params, found),
+                        GenericArgument::AssocType(assoc) => visit_type(&assoc.ty, params, found),
+                        _ => {}
+                    }
+                }
+            }
+            PathArguments::Parenthesized(ref args) => {
+                for input in &args.inputs {
+                    visit_type(input, params, found);
+                }
+                if let ReturnType::Type(_, ref output) = args.output {
+                    visit_type(output, params, found);
+                }
+            }
+            PathArguments::None => {}
+        }
+    }
+}
+
+fn visit_bounds(
+    bounds: &Punctuated<TypeParamBound, syn::token::Plus>,
+    params: &[&Ident],
+    found: &mut Vec<Type>,
+) {
+    for bound in bounds {
+        if let TypeParamBound::Trait(bound) = bound {
+            visit_path(&bound.path, params, found);
+        }
+    }
+}
+
+/// Falls back to bounding every parameter mentioned in `tokens`, such as in a macro type.
+fn visit_tokens(tokens: TokenStream, params: &[&Ident], found: &mut Vec<Type>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                if let Some(param) = params.iter().copied().find(|param| ident == **param) {
+                    insert(found, syn::parse_quote!(#param));
+                }
+            }
+            TokenTree::Group(group) => visit_tokens(group.stream(), params, found),
+            _ => {}
+        }
+    }
+}
+
+fn insert(found: &mut Vec<Type>, ty: Type) {
+    let key = ty.to_token_stream().to_string();
+    if !found
+        .iter()
+        .any(|found| found.to_token_stream().to_string() == key)
+    {
+        found.push(ty);
+    }
+}
@@ -0,0 +1,387 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Field rules checked by `validate_deser` while deserializing.
+//!
+//! A `#[validate(field = path::to_fn)]` rule is checked by a `deserialize_with` function of the
+//! helper, so a broken rule fails deserialization at the position of the offending value rather
+//! than after the whole input is read. The same function enforces `#[validate(max_items = N)]`
+//! while the field is visited, before the extra elements are allocated, and applies the
+//! `#[sanitizer(...)]` attributes of the field before any rule sees it.
+//!
+//! A field with an `#[validate(on_invalid = "...")]` policy is repaired by its function when it
+//! breaks its rules. `nested` fields go through a function too, so the repairs made inside their
+//! value are located under the field.
+
+use crate::attrs::{FieldAttrs, OnInvalid, Rule, SanitizeAttrs};
+use crate::sanitize;
+use crate::serde_attrs::{is_serde_attr, serde_metas};
+use crate::validate::{self, is_option, option_inner, Mode};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, Data, DeriveInput, Expr, ExprLit, ExprPath, Field, Fields, Lit, Meta, Path,
+};
+
+/// The helper input with its checked fields deserialized through the generated functions.
+pub(crate) struct FieldChecks {
+    pub(crate) helper_input: DeriveInput,
+    /// The inherent impl of the helper defining the `deserialize_with` functions.
+    pub(crate) checks_impl: TokenStream,
+}
+
+pub(crate) fn prepare(
+    input: &DeriveInput,
+    helper_name: &Ident,
+    krate: &Path,
+    serde: &Path,
+    map_err: &TokenStream,
+) -> syn::Result<FieldChecks> {
+    let mut helper_input = input.clone();
+    let mut functions = Vec::new();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+
+    let fields: Vec<&mut Fields> = match helper_input.data {
+        Data::Struct(ref mut data) => vec![&mut data.fields],
+        Data::Enum(ref mut data) => data
+            .variants
+            .iter_mut()
+            .map(|variant| &mut variant.fields)
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    for (i, field) in fields
+        .into_iter()
+        .flat_map(|fields| fields.iter_mut().enumerate())
+    {
+        let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+        let mut checks = Vec::new();
+        let mut max_items = None;
+        let mut nested = false;
+        for rule in &attrs.rules {
+            match rule {
+                Rule::Field(path) => checks.push(path),
+                Rule::MaxItems(max) => max_items = Some(max),
+                Rule::Nested => nested = true,
+                _ => {}
+            }
+        }
+        let sanitizers = SanitizeAttrs::from_attrs(&field.attrs)?.sanitizers;
+        let mut metas = serde_metas(&field.attrs)?;
+        // A nested field only goes through a function to locate the repairs of its value.
+        let locates_repairs = nested
+            && !metas.iter().any(|meta| {
+                ["flatten", "skip", "skip_deserializing"]
+                    .iter()
+                    .any(|name| meta.path().is_ident(name))
+            });
+        if checks.is_empty()
+            && max_items.is_none()
+            && sanitizers.is_empty()
+            && attrs.on_invalid.is_none()
+            && !locates_repairs
+        {
+            continue;
+        }
+
+        let function = format_ident!("__validate_field_{}", functions.len());
+        let ty = &field.ty;
+        let segment = validate::segment(i, field);
+
+        let with = take_with(&mut metas)?;
+        if with.is_some() && attrs.on_invalid == Some(OnInvalid::Skip) {
+            return Err(syn::Error::new_spanned(
+                &*field,
+                "`on_invalid = \"skip\"` cannot be combined with a `with` or `deserialize_with` \
+                 function, the elements are deserialized one by one",
+            ));
+        }
+        let bound = quote! { #ty: #serde::Deserialize<'de> };
+        let (deserialize, function_bound) = match with {
+            Some(ref deserialize) => (deserialize.clone(), None),
+            None => (
+                quote! { <#ty as #serde::Deserialize<'de>>::deserialize(deserializer) },
+                Some(&bound),
+            ),
+        };
+        let limit = max_items.map(|max| {
+            quote! {
+                let deserializer = #krate::de::Limited::new(deserializer).with_max_items(#max);
+            }
+        });
+        let sanitize = sanitize::field_stmt(&sanitizers, ty, &quote! { &mut value }, krate);
+        // Invalid elements are skipped while deserializing, as they are visited.
+        let policy = attrs.on_invalid.filter(|policy| *policy != OnInvalid::Skip);
+        let repair = policy.map(|policy| {
+            let repair = repair(policy, field, &attrs.rules, &segment, krate, map_err);
+            quote! {
+                if !#krate::__private::skips_repairs() {
+                    #repair
+                }
+            }
+        });
+        let mutability = (sanitize.is_some() || repair.is_some()).then(|| quote! { mut });
+        let (deserialize, skip_bound) = match attrs.on_invalid {
+            Some(OnInvalid::Skip) => (
+                quote! {
+                    if #krate::__private::skips_repairs() {
+                        #deserialize
+                    } else {
+                        <#ty as #krate::__private::SkipInvalid<'de>>::deserialize_skipping(
+                            deserializer,
+                        )
+                    }
+                },
+                Some(quote! { #ty: #krate::__private::SkipInvalid<'de> }),
+            ),
+            // The value is validated once deserialized, so its own checks wait until then
+            // rather than fail before it could be repaired. The checks of the values it is made
+            // of still fail it.
+            Some(OnInvalid::Default) if nested => {
+                let target = option_inner(ty).unwrap_or(ty);
+                let deserialize = quote! {
+                    #krate::__private::defer_validation::<#target, _>(|| #deserialize)
+                };
+                (deserialize, None)
+            }
+            _ => (deserialize, None),
+        };
+        let skip = (!checks.is_empty()).then(|| {
+            quote! {
+                if #krate::__private::skips_field_checks() {
+                    return ::core::result::Result::Ok(value);
+                }
+            }
+        });
+        let validation = if checks.is_empty() {
+            quote! { ::core::result::Result::Ok(value) }
+        } else {
+            let checks = checks.iter().map(|path| {
+                quote! { __errors.nest(#segment, #path(__value)); }
+            });
+            let checks = if is_option(ty) {
+                quote! {
+                    if let ::core::option::Option::Some(__value) = &value {
+                        #( #checks )*
+                    }
+                }
+            } else {
+                quote! {
+                    let __value = &value;
+                    #( #checks )*
+                }
+            };
+            quote! {
+                let mut __errors = #krate::ValidationErrors::new();
+                #checks
+                match __errors.into_result() {
+                    ::core::result::Result::Ok(()) => ::core::result::Result::Ok(value),
+                    ::core::result::Result::Err(errors) => {
+                        ::core::result::Result::Err(#krate::__private::map_validation_error(
+                            errors, #map_err,
+                        ))
+                    }
+                }
+            }
+        };
+        let bounds = function_bound.into_iter().chain(&skip_bound);
+        functions.push(quote! {
+            fn #function<'de, __D>(deserializer: __D) -> ::core::result::Result<#ty, __D::Error>
+            where
+                __D: #serde::Deserializer<'de>,
+                #( #bounds, )*
+            {
+                #limit
+                let __repairs = #krate::__private::repairs_len();
+                let #mutability value: #ty = #deserialize?;
+                #krate::__private::nest_repairs(__repairs, #segment);
+                #sanitize
+                #repair
+                #skip
+                #validation
+            }
+        });
+
+        let deserialize_with = quote! { #helper_name #turbofish :: #function }.to_string();
+        metas.push(parse_quote!(deserialize_with = #deserialize_with));
+        // serde defaults a missing `Option` to `None`, but not once it goes through a function.
+        if is_option(ty)
+            && with.is_none()
+            && !metas.iter().any(|meta| meta.path().is_ident("default"))
+        {
+            metas.push(parse_quote!(default));
+        }
+        // serde infers no bound for fields deserialized with a function.
+        if with.is_none() && !metas.iter().any(|meta| meta.path().is_ident("bound")) {
+            let bound = bound.to_string();
+            metas.push(parse_quote!(bound(deserialize = #bound)));
+        }
+        field.attrs.retain(|attr| !is_serde_attr(attr));
+        field.attrs.push(parse_quote!(#[serde(#(#metas),*)]));
+    }
+
+    let checks_impl = if functions.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics #helper_name #ty_generics #where_clause {
+                #( #functions )*
+            }
+        }
+    };
+    Ok(FieldChecks {
+        helper_input,
+        checks_impl,
+    })
+}
+
+/// Generates the statements repairing `value` with `policy` if it breaks the rules of its field,
+/// recording the repair.
+fn repair(
+    policy: OnInvalid,
+    field: &Field,
+    rules: &[Rule],
+    segment: &TokenStream,
+    krate: &Path,
+    map_err: &TokenStream,
+) -> TokenStream {
+    match policy {
+        OnInvalid::Default => {
+            // Conditional rules need the rest of the instance, which is not deserialized yet, and
+            // a missing value cannot be repaired by its default.
+            let rules: Vec<_> = rules
+                .iter()
+                .filter(|rule| !rule.is_async() && !rule.is_grouped() && !rule.is_conditional())
+                .filter(|rule| !matches!(rule, Rule::Required))
+                .collect();
+            let ty = &field.ty;
+            let check =
+                |access| validate::field_check(field, &rules, None, &access, krate, Mode::Sync);
+            let (check, check_default) = (check(quote! { &value }), check(quote! { &__default }));
+            let action = quote! { #krate::RepairAction::Default };
+            // A default breaking the rules too cannot repair the value, which fails as it is.
+            quote! {
+                let mut __errors = #krate::ValidationErrors::new();
+                { #check }
+                if let ::core::result::Result::Err(errors) = __errors.into_result() {
+                    let __default: #ty = ::core::default::Default::default();
+                    let mut __errors = #krate::ValidationErrors::new();
+                    { #check_default }
+                    if !__errors.is_empty() {
+                        let mut __errors = #krate::ValidationErrors::new();
+                        __errors.extend_at(#segment, errors);
+                        return ::core::result::Result::Err(
+                            #krate::__private::map_validation_error(__errors, #map_err),
+                        );
+                    }
+                    value = __default;
+                    #krate::__private::record_repair(#segment, #action, errors);
+                }
+            }
+        }
+        OnInvalid::Skip => unreachable!("invalid elements are skipped while deserializing"),
+        OnInvalid::Clamp => {
+            let clamps = rules.iter().filter_map(|rule| match rule {
+                Rule::Range { min, max } => Some((rule, min, max)),
+                _ => None,
+            });
+            let clamps = clamps.map(|(rule, min, max)| {
+                let check = validate::rule_check(rule, &quote! { &*__value }, krate);
+                let min = min.as_ref().map(|min| {
+                    quote! {
+                        let __min = #min;
+                        if !::core::matches!(
+                            ::core::cmp::PartialOrd::partial_cmp(&*__value, &__min),
+                            ::core::option::Option::Some(
+                                ::core::cmp::Ordering::Equal | ::core::cmp::Ordering::Greater
+                            )
+                        ) {
+                            *__value = __min;
+                        }
+                    }
+                });
+                let max = max.as_ref().map(|max| {
+                    quote! {
+                        let __max = #max;
+                        if !::core::matches!(
+                            ::core::cmp::PartialOrd::partial_cmp(&*__value, &__max),
+                            ::core::option::Option::Some(
+                                ::core::cmp::Ordering::Less | ::core::cmp::Ordering::Equal
+                            )
+                        ) {
+                            *__value = __max;
+                        }
+                    }
+                });
+                quote! {
+                    if let ::core::result::Result::Err(error) = #check {
+                        #min
+                        #max
+                        #krate::__private::record_repair(
+                            #segment,
+                            #krate::RepairAction::Clamp,
+                            ::core::convert::Into::into(error),
+                        );
+                    }
+                }
+            });
+            if is_option(&field.ty) {
+                quote! {
+                    if let ::core::option::Option::Some(__value) = &mut value {
+                        #( #clamps )*
+                    }
+                }
+            } else {
+                quote! {
+                    let __value = &mut value;
+                    #( #clamps )*
+                }
+            }
+        }
+    }
+}
+
+/// Removes the `deserialize_with` or `with` attribute of a field, returning the expression that
+/// deserializes it the way the attribute asked for.
+fn take_with(metas: &mut Vec<Meta>) -> syn::Result<Option<TokenStream>> {
+    let Some(position) = metas
+        .iter()
+        .position(|meta| meta.path().is_ident("deserialize_with") || meta.path().is_ident("with"))
+    else {
+        return Ok(None);
+    };
+    let meta = metas.remove(position);
+    let path: ExprPath = match meta {
+        Meta::NameValue(ref name_value) => match name_value.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(ref path),
+                ..
+            }) => path.parse()?,
+            ref value => return Err(syn::Error::new_spanned(value, "expected a string literal")),
+        },
+        _ => return Err(syn::Error::new_spanned(meta, "expected a string literal")),
+    };
+    Ok(Some(if meta.path().is_ident("with") {
+        quote! { #path::deserialize(deserializer) }
+    } else {
+        quote! { #path(deserializer) }
+    }))
+}
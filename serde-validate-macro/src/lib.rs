@@ -19,112 +19,683 @@
 
 //! # serde-validate-macro
 //!
-//! This crate provides the `validate_deser` procedural serde-validate-macro for the `serde-validate` crate.
+//! This crate provides the `validate_deser` procedural serde-validate-macro and the `Validate` derive
+//! macro for the `serde-validate` crate.
 //!
 //! Users should prefer using the `serde-validate` crate.
 
 extern crate proc_macro;
+
+mod attrs;
+mod bound;
+mod field_checks;
+mod sanitize;
+mod serde_attrs;
+mod validate;
+
+use attrs::{is_sanitize_attr, is_validate_attr, CratePaths, DeserArgs};
+use bound::Direction;
+use field_checks::FieldChecks;
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
 use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
 use syn::{
-    parse_macro_input, Data, DeriveInput, Field, Fields, GenericParam, Generics, Index, Variant,
+    parse_macro_input, parse_quote, Data, DeriveInput, Expr, ExprLit, ExprPath, Field, Fields,
+    Generics, Index, Lit, LitStr, Meta, Variant,
 };
+use validate::Mode;
 
 /// Attribute serde-validate-macro to derive deserialization with validation for a struct or enum.
 ///
 /// This serde-validate-macro generates a helper struct to deserialize the original struct or enum and
 /// then validates the deserialized data using the `serde_validate::Validate` trait. If validation fails,
-/// a deserialization error is returned.
+/// a deserialization error is returned. The original `Validate::Error` can be recovered with
+/// `serde_validate::from_deserializer`.
+///
+/// Field attributes used by `#[derive(Validate)]` are not copied to the helper, so both macros
+/// can be combined on the same type.
+///
+/// The `#[serde(...)]` attributes of the type, its variants and its fields are moved to the
+/// helper, so they behave as with `#[derive(Deserialize)]`. They are kept on the original type
+/// only if another serde derive still reads them. The container attributes `from` and `try_from`
+/// are not supported.
+///
+/// Fields marked `#[validate(field = path::to_fn)]` are also checked as soon as they are
+/// deserialized, before the rest of the input is read, so the error carries the position of the
+/// offending value. These checks are reported with the `map_err` function, or
+/// `serde::de::Error::custom` by default; when `Validate::Error` is `ValidationErrors`,
+/// `serde_validate::from_deserializer` recovers them too.
+/// Likewise, `#[validate(max_items = N)]` fields are rejected as soon as they hold too many
+/// elements, before the rest are allocated.
+///
+/// The `#[sanitizer(...)]` attributes of `#[derive(Sanitize)]` are applied to each field right
+/// after it is deserialized, so the rules, field checks included, only see sanitized values.
+///
+/// Fields marked `#[validate(on_invalid = "...")]` are repaired instead of failing
+/// deserialization when they break their rules: `"default"` replaces the value by its default,
+/// unless the default breaks them too, `"skip"` drops the invalid elements of a collection and
+/// `"clamp"` clamps the value into the bounds of its `range` rule. The repairs are reported as
+/// warnings by `serde_validate::from_deserializer_with_warnings`. Only the rules of the repaired
+/// value itself, or of the skipped element, are repaired: the values it is made of still fail
+/// deserialization when they break their own rules.
+///
+/// Validation, field checks and repairs included, is skipped when deserializing trusted input
+/// through `serde_validate::de::deserialize_unvalidated` or `serde_validate::de::Unchecked`. The
+/// `max_items` limits still apply.
+///
+/// Lifetime parameters are tied to the deserializer's, so borrowed fields such as `&'a str` or
+/// `#[serde(borrow)] Cow<'a, str>` are deserialized zero-copy.
+///
+/// # Arguments
+///
+/// - `crate = "path::to::serde_validate"`: the path of the `serde_validate` crate, for use through
+///   a crate re-exporting it. Defaults to `::serde_validate`.
+/// - `serde = "path::to::serde"`: the path of the `serde` crate. Defaults to the one re-exported by
+///   `serde_validate`, so no direct `serde` dependency is needed.
+/// - `bound = "T: MyTrait"`: replaces the inferred where predicates of the generated impl, like
+///   serde's `bound`. By default, only the type parameters deserialized by serde are bounded.
+/// - `map_err = path::to_fn`: converts the validation error with
+///   `fn<E: serde::de::Error>(&Validate::Error) -> E` instead of `serde::de::Error::custom`, so
+///   richer serde error kinds can be reported. `serde_validate::de` provides ready-made functions.
+///   Field rules fail with the `serde_validate::ValidationErrors` of the field, so with field
+///   rules the function must also accept those.
+/// - `group = "name"`: validates with `Validate::validate_group` for the named group instead of
+///   `Validate::validate`. To pick the group at runtime, use `context = serde_validate::Group`.
+/// - `context = Ctx`: validates with `serde_validate::ValidateWith<Ctx>` instead of `Validate`.
+///   No `Deserialize` impl is generated; the type implements `serde_validate::de::DeserializeWith`
+///   instead, and is deserialized through the `serde_validate::de::WithContext` seed.
+#[proc_macro_attribute]
+pub fn validate_deser(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let mut deser_args = DeserArgs::default();
+    let args_parser = syn::meta::parser(|meta| deser_args.parse(meta));
+
+    let deser = args_parser
+        .parse(args)
+        .and_then(|_| expand_deser(&deser_args, &input))
+        .unwrap_or_else(syn::Error::into_compile_error);
+
+    let original = serde_attrs::original(&input);
+
+    let tokens = quote! {
+        #original
+
+        #deser
+    };
+
+    tokens.into()
+}
+
+/// Attribute serde-validate-macro to derive serialization with validation for a struct or enum.
+///
+/// This serde-validate-macro generates a `Serialize` implementation that validates the instance
+/// using the `serde_validate::Validate` trait before serializing it, so invalid values are never
+/// emitted. If validation fails, a serialization error is returned.
+///
+/// As with `validate_deser`, the `#[serde(...)]` attributes are moved to the generated helper. The
+/// container attribute `into` is not supported.
+///
+/// It accepts the `crate`, `serde` and `group` arguments of `validate_deser`.
 #[proc_macro_attribute]
-pub fn validate_deser(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn validate_ser(args: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
+    let mut paths = CratePaths::default();
+    let mut group = None;
+    let args_parser = syn::meta::parser(|meta| {
+        if paths.parse(&meta)? {
+            Ok(())
+        } else if meta.path.is_ident("group") {
+            group = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unknown `validate_ser` argument"))
+        }
+    });
+
+    let ser = args_parser
+        .parse(args)
+        .and_then(|_| expand_ser(&paths, group.as_ref(), &input))
+        .unwrap_or_else(syn::Error::into_compile_error);
+
+    let original = serde_attrs::original(&input);
+
+    let tokens = quote! {
+        #original
+
+        #ser
+    };
+
+    tokens.into()
+}
+
+/// Attribute serde-validate-macro combining `validate_deser` and `validate_ser`.
+///
+/// It accepts the same arguments as `validate_deser`, except `context`, since serialization has no
+/// context to validate against, and `bound`, for which `#[serde(bound(...))]` can be used instead.
+#[proc_macro_attribute]
+pub fn validate_serde(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let mut deser_args = DeserArgs::default();
+    let args_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("context") {
+            return Err(meta.error("`context` is not supported by `validate_serde`"));
+        }
+        if meta.path.is_ident("bound") {
+            return Err(meta.error(
+                "`bound` is not supported by `validate_serde`, use `#[serde(bound(...))]` instead",
+            ));
+        }
+        deser_args.parse(meta)
+    });
+
+    let serde = args_parser
+        .parse(args)
+        .and_then(|_| {
+            let deser = expand_deser(&deser_args, &input)?;
+            let ser = expand_ser(&deser_args.paths, deser_args.group.as_ref(), &input)?;
+            Ok(quote! {
+                #deser
+
+                #ser
+            })
+        })
+        .unwrap_or_else(syn::Error::into_compile_error);
+
+    let original = serde_attrs::original(&input);
+
+    let tokens = quote! {
+        #original
+
+        #serde
+    };
+
+    tokens.into()
+}
+
+/// Generates the deserialization helper of `input` and the validating `Deserialize` impl.
+fn expand_deser(
+    deser_args: &DeserArgs,
+    input: &DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    check_not_derived(input, "Deserialize", "validate_deser")?;
+    if let (Some(_), Some(group)) = (&deser_args.context, &deser_args.group) {
+        return Err(syn::Error::new_spanned(
+            group,
+            "`group` cannot be combined with `context`, use `context = serde_validate::Group` to \
+             pick the group at runtime",
+        ));
+    }
+
+    let krate = deser_args.paths.krate();
+    let serde = deser_args.paths.serde();
+
     let name = &input.ident;
 
     let generics = &input.generics;
 
     let helper_name = Ident::new(&format!("__ValidDeserialize{name}"), name.span());
 
+    let map_err = match deser_args.map_err {
+        Some(ref map_err) => quote! { #map_err },
+        None => quote! { #krate::de::custom },
+    };
+
+    let FieldChecks {
+        helper_input,
+        checks_impl,
+    } = field_checks::prepare(input, &helper_name, &krate, &serde, &map_err)?;
     let HelperData {
         helper_def,
         init_from_helper,
-    } = match input.data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                build_named_struct(&name, &helper_name, &generics, &fields.named)
-            }
-            Fields::Unnamed(ref fields) => {
-                build_unnamed_struct(&name, &helper_name, &generics, &fields.unnamed)
-            }
-            Fields::Unit => build_unit_struct(&name, &helper_name),
-        },
-        Data::Enum(ref data) => build_enum(&name, &helper_name, &generics, &data.variants),
-        Data::Union(_) => {
-            unimplemented!()
-        }
+    } = build_helper(&helper_input, &helper_name, "validate_deser")?;
+
+    let mut container_metas =
+        helper_container_metas(input, &serde, &["from", "try_from"], "validate_deser")?;
+    let (default_impl, default_bound) = match container_metas
+        .iter_mut()
+        .find(|meta| meta.path().is_ident("default"))
+    {
+        Some(meta) => helper_default(input, &helper_name, meta)?,
+        None => (quote! {}, None),
     };
 
-    let generic_params = generics.params.to_token_stream();
-    let extra_where_clause: Vec<_> = generics
-        .params
+    if let Some(ref bound) = deser_args.bound {
+        if !container_metas
+            .iter()
+            .any(|meta| meta.path().is_ident("bound"))
+        {
+            let bound = bound.to_token_stream().to_string();
+            container_metas.push(parse_quote!(bound(deserialize = #bound)));
+        }
+    }
+
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    let (impl_generics, _, _) = de_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let mut predicates: Vec<_> = generics
+        .where_clause
         .iter()
-        .filter_map(|p| match p {
-            GenericParam::Type(p) => {
-                let p = &p.ident;
-                Some(quote! { #p : serde::Deserialize<'__de> })
-            }
-            _ => None,
-        })
+        .flat_map(|clause| clause.predicates.iter())
+        .map(ToTokens::to_token_stream)
         .collect();
-    let where_clause = match generics.where_clause {
+    match deser_args.bound {
+        Some(ref bound) => predicates.extend(bound.iter().map(ToTokens::to_token_stream)),
+        None => predicates.extend(bound::predicates(
+            input,
+            Direction::Deserialize,
+            &quote! { #serde::Deserialize<'de> },
+        )?),
+    }
+    // The helper borrows from the input for as long as the lifetimes of the original type.
+    predicates.extend(generics.lifetimes().map(|p| {
+        let lifetime = &p.lifetime;
+        quote! { 'de: #lifetime }
+    }));
+    predicates.extend(default_bound);
+    let validate_trait = match deser_args.context {
+        None => quote! { #krate::Validate },
+        Some(ref context) => quote! { #krate::ValidateWith<#context> },
+    };
+    predicates.push(quote! { #name #ty_generics: #validate_trait });
+    if deser_args.map_err.is_none() {
+        predicates.push(quote! {
+            <#name #ty_generics as #validate_trait>::Error: ::core::fmt::Display
+        });
+    }
+    let where_clause = quote! { where #(#predicates,)* };
+
+    let validated = match deser_args.group {
+        Some(ref group) => quote! {
+            #krate::Validate::validate_group(&instance, #krate::Group::new(#group))
+                .map(|()| instance)
+        },
+        None => quote! { <Self as #validate_trait>::validated(instance) },
+    };
+    let group = match deser_args.group {
+        Some(ref group) => quote! { ::core::option::Option::Some(#krate::Group::new(#group)) },
+        None => quote! { ::core::option::Option::None },
+    };
+    let deser_impl = match deser_args.context {
         None => quote! {
-            where #(#extra_where_clause,)*
+            impl #impl_generics #serde::Deserialize<'de> for #name #ty_generics #where_clause {
+                fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: #serde::Deserializer<'de>
+                {
+                    let __frame = #krate::__private::Frame::enter::<Self>(#group);
+                    let helper = <#helper_name #ty_generics as #serde::Deserialize<'de>>::deserialize(deserializer)?;
+                    let instance = #init_from_helper;
+                    if #krate::__private::skips_validation() {
+                        return ::core::result::Result::Ok(instance);
+                    }
+                    #validated.map_err(|err| #map_err(&err))
+                }
+            }
         },
-        Some(ref clause) => {
-            let predicates = clause.predicates.iter().map(|p| p.to_token_stream());
-            quote! {
-                where #(#predicates,)* #(#extra_where_clause,)*
+        Some(ref context) => quote! {
+            impl #impl_generics #krate::de::DeserializeWith<'de, #context> for #name #ty_generics #where_clause {
+                fn deserialize_with<__D>(deserializer: __D, context: &#context) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: #serde::Deserializer<'de>
+                {
+                    let __frame = #krate::__private::Frame::enter::<Self>(#group);
+                    let helper = <#helper_name #ty_generics as #serde::Deserialize<'de>>::deserialize(deserializer)?;
+                    let instance = #init_from_helper;
+                    if #krate::__private::skips_validation() {
+                        return ::core::result::Result::Ok(instance);
+                    }
+                    <Self as #validate_trait>::validated_with(instance, context)
+                        .map_err(|err| #map_err(&err))
+                }
             }
-        }
+        },
     };
-    let simple_gen_params = generics.params.iter().map(|p| match p {
-        GenericParam::Type(p) => {
-            let p = &p.ident;
-            quote! { #p }
-        }
-        GenericParam::Lifetime(p) => {
-            let p = &p.lifetime;
-            quote! { #p }
-        }
-        GenericParam::Const(p) => {
-            let p = &p.ident;
-            quote! { #p }
+
+    Ok(quote! {
+        #[derive(#serde::Deserialize)]
+        #[serde(#(#container_metas),*)]
+        #helper_def
+
+        #checks_impl
+
+        #default_impl
+
+        #deser_impl
+    })
+}
+
+/// Generates the serialization helper of `input` and the validating `Serialize` impl.
+///
+/// The helper is a serde remote definition of `input`, so it serializes the original type
+/// directly, without copying it.
+fn expand_ser(
+    paths: &CratePaths,
+    group: Option<&LitStr>,
+    input: &DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    check_not_derived(input, "Serialize", "validate_ser")?;
+
+    let krate = paths.krate();
+    let serde = paths.serde();
+
+    let name = &input.ident;
+
+    let generics = &input.generics;
+
+    let helper_name = Ident::new(&format!("__ValidSerialize{name}"), name.span());
+
+    let HelperData { helper_def, .. } = build_helper(input, &helper_name, "validate_ser")?;
+
+    let container_metas = helper_container_metas(input, &serde, &["into"], "validate_ser")?;
+
+    let remote = name.to_string();
+
+    let validate = match group {
+        Some(group) => {
+            quote! { #krate::Validate::validate_group(self, #krate::Group::new(#group)) }
         }
-    });
+        None => quote! { #krate::Validate::validate(self) },
+    };
 
-    let tokens = quote! {
-        #input
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let mut predicates: Vec<_> = generics
+        .where_clause
+        .iter()
+        .flat_map(|clause| clause.predicates.iter())
+        .map(ToTokens::to_token_stream)
+        .collect();
+    predicates.extend(bound::predicates(
+        input,
+        Direction::Serialize,
+        &quote! { #serde::Serialize },
+    )?);
+    predicates.push(quote! { #name #ty_generics: #krate::Validate });
+    predicates.push(quote! {
+        <#name #ty_generics as #krate::Validate>::Error: ::core::fmt::Display
+    });
 
-        #[derive(serde::Deserialize)]
+    Ok(quote! {
+        #[derive(#serde::Serialize)]
+        #[serde(remote = #remote, #(#container_metas),*)]
         #helper_def
 
-        impl <'__de, #generic_params> serde::Deserialize<'__de> for #name<#(#simple_gen_params,)*> #where_clause {
-            fn deserialize<__D>(deserializer: __D) -> Result<Self, __D::Error>
+        impl #impl_generics #serde::Serialize for #name #ty_generics
+        where
+            #(#predicates,)*
+        {
+            fn serialize<__S>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error>
             where
-                __D: serde::Deserializer<'__de>
+                __S: #serde::Serializer
             {
-                let helper = #helper_name::deserialize(deserializer)?;
-                let instance = #init_from_helper;
-                instance.validated().map_err(serde::de::Error::custom)
+                #validate.map_err(#serde::ser::Error::custom)?;
+                #helper_name::serialize(self, serializer)
             }
         }
+    })
+}
+
+/// Fails if `input` already derives `trait_name`, which would conflict with the generated impl.
+fn check_not_derived(input: &DeriveInput, trait_name: &str, macro_name: &str) -> syn::Result<()> {
+    match serde_attrs::derived_paths(&input.attrs)
+        .find(|path| path.segments.last().is_some_and(|s| s.ident == trait_name))
+    {
+        Some(path) => Err(syn::Error::new_spanned(
+            path,
+            format!(
+                "`#[{macro_name}]` generates the `{trait_name}` impl, remove `{trait_name}` from this derive"
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns the serde container attributes of the helper of `input`, failing on the `unsupported`
+/// ones.
+///
+/// The helper is renamed after the original type, so serde reports the original name, and is
+/// derived through the `serde` path.
+fn helper_container_metas(
+    input: &DeriveInput,
+    serde: &syn::Path,
+    unsupported: &[&str],
+    macro_name: &str,
+) -> syn::Result<Vec<Meta>> {
+    let mut metas = serde_attrs::serde_metas(&input.attrs)?;
+    if let Some(meta) = metas
+        .iter()
+        .find(|meta| unsupported.iter().any(|name| meta.path().is_ident(name)))
+    {
+        let attr = meta.path().to_token_stream();
+        return Err(syn::Error::new_spanned(
+            meta,
+            format!("`#[serde({attr})]` is not supported by `#[{macro_name}]`"),
+        ));
+    }
+    if !metas.iter().any(|meta| meta.path().is_ident("rename")) {
+        let name = input.ident.unraw().to_string();
+        metas.push(parse_quote!(rename = #name));
+    }
+    if !metas.iter().any(|meta| meta.path().is_ident("crate")) {
+        let serde = serde.to_token_stream().to_string();
+        metas.push(parse_quote!(crate = #serde));
+    }
+    Ok(metas)
+}
 
+/// Makes the container `default` of the deserialization helper use the default of the original
+/// type, converted into the helper.
+///
+/// For a bare `default`, this implements `Default` for the helper and returns the bound that serde
+/// would have required on the original type. For `default = "path"`, `meta` is rewritten to call a
+/// generated function wrapping `path`.
+fn helper_default(
+    input: &DeriveInput,
+    helper_name: &Ident,
+    meta: &mut Meta,
+) -> syn::Result<(proc_macro2::TokenStream, Option<proc_macro2::TokenStream>)> {
+    let conversion = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => {
+                let names = fields.named.iter().map(|field| &field.ident);
+                quote! { #helper_name { #( #names: __default.#names ),* } }
+            }
+            Fields::Unnamed(ref fields) => {
+                let indices = (0..fields.unnamed.len()).map(Index::from);
+                quote! { #helper_name( #( __default.#indices ),* ) }
+            }
+            // serde reports the misuse itself.
+            Fields::Unit => return Ok((quote! {}, None)),
+        },
+        _ => return Ok((quote! {}, None)),
     };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    match meta {
+        Meta::Path(_) => {
+            let bound = quote! { #name #ty_generics: ::core::default::Default };
+            let predicates = where_clause.map(|clause| &clause.predicates);
+            let default_impl = quote! {
+                impl #impl_generics ::core::default::Default for #helper_name #ty_generics
+                where
+                    #predicates #bound
+                {
+                    fn default() -> Self {
+                        let __default: #name #ty_generics = ::core::default::Default::default();
+                        #conversion
+                    }
+                }
+            };
+            Ok((default_impl, Some(bound)))
+        }
+        Meta::NameValue(ref mut name_value) => {
+            let path: ExprPath = match name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref path),
+                    ..
+                }) => path.parse()?,
+                ref value => {
+                    return Err(syn::Error::new_spanned(value, "expected a string literal"))
+                }
+            };
+            let function = format!("{helper_name}::__default");
+            name_value.value = parse_quote!(#function);
+            let default_impl = quote! {
+                impl #impl_generics #helper_name #ty_generics #where_clause {
+                    fn __default() -> Self {
+                        let __default: #name #ty_generics = #path();
+                        #conversion
+                    }
+                }
+            };
+            Ok((default_impl, None))
+        }
+        Meta::List(ref list) => Err(syn::Error::new_spanned(
+            list,
+            "expected `default` or `default = \"...\"`",
+        )),
+    }
+}
 
-    tokens.into()
+/// Builds the helper type mirroring the shape of `input`.
+fn build_helper(
+    input: &DeriveInput,
+    helper_name: &Ident,
+    macro_name: &str,
+) -> syn::Result<HelperData> {
+    let name = &input.ident;
+    let generics = &input.generics;
+    Ok(match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => {
+                build_named_struct(name, helper_name, generics, &fields.named)
+            }
+            Fields::Unnamed(ref fields) => {
+                build_unnamed_struct(name, helper_name, generics, &fields.unnamed)
+            }
+            Fields::Unit => build_unit_struct(name, helper_name),
+        },
+        Data::Enum(ref data) => build_enum(name, helper_name, generics, &data.variants),
+        Data::Union(ref data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                format!("`#[{macro_name}]` cannot be used on unions"),
+            ))
+        }
+    })
+}
+
+/// Derive macro generating a `serde_validate::Validate` implementation from `#[validate(...)]`
+/// attributes.
+///
+/// The generated `Validate::Error` is `serde_validate::ValidationErrors`, and every broken rule is
+/// reported under the path of its field.
+///
+/// Field rules:
+/// - `range(min = ..., max = ...)`: the value lies within the inclusive bounds.
+/// - `length(min = ..., max = ...)`: the length (in characters for strings) lies within the
+///   inclusive bounds.
+/// - `custom = path::to_fn`: calls `fn(&T) -> Result<(), E>` where `E: Into<ValidationErrors>`.
+/// - `field = path::to_fn`: like `custom`, but `validate_deser` also checks it while
+///   deserializing the field.
+/// - `max_items = N`: the collection has at most `N` elements. `validate_deser` also enforces it
+///   while deserializing the field, failing before the extra elements are deserialized.
+/// - `nested`: validates the field with its own `Validate` implementation, merging its errors
+///   under the field. The field's `Validate::Error` must be convertible into `ValidationErrors`.
+/// - `required`: the `Option<T>` field is set.
+///
+/// Rules on `Option<T>` fields are only checked when the value is present, except `required`.
+///
+/// The rules of a `#[validate(..., when = path::to_fn)]` attribute, on a field or the container,
+/// are only checked when `fn(&Self) -> bool` returns `true`, so they can depend on the other
+/// fields.
+///
+/// The rules of a `#[validate(..., warn)]` attribute, on a field or the container, are only
+/// warnings: they are reported by `Validate::warnings` and never fail validation. The warnings of
+/// `nested` fields are reported too, under the field.
+///
+/// The rules of a `#[validate(..., groups = ["name", ...])]` attribute, on a field or the
+/// container, are only checked by `Validate::validate_group` with one of the named groups. The
+/// rules of no group are always checked, and `nested` fields are validated for the same group.
+///
+/// A field can also declare how `validate_deser` repairs it when it breaks its rules, with
+/// `on_invalid = "default"`, `"skip"` or `"clamp"`. The derived `Validate` ignores it.
+///
+/// Container rules:
+/// - `custom = path::to_fn`: calls `fn(&Self) -> Result<(), E>` where `E: Into<ValidationErrors>`.
+/// - `required_if(field = name, when = path::to_fn)`: the `Option<T>` field `name` is set when
+///   `fn(&Self) -> bool` returns `true`.
+/// - `required_unless(field = name, when = path::to_fn)`: the same, when it returns `false`.
+/// - `exactly_one_of(name, ...)`: exactly one of the `Option<T>` fields is set. The error is
+///   reported under every field if none is set, or under every set field otherwise.
+///
+/// The rules naming fields only apply to structs.
+///
+/// The container also accepts `crate = "path::to::serde_validate"`, as `validate_deser` does.
+///
+/// The asynchronous rules of `#[derive(AsyncValidate)]` are ignored.
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    validate::derive(&input, Mode::Sync)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive macro generating a `serde_validate::AsyncValidate` implementation from `#[validate(...)]`
+/// attributes.
+///
+/// It accepts the same rules as `#[derive(Validate)]`, plus:
+/// - `custom_async = path::to_fn`: awaits `fn(&T) -> impl Future<Output = Result<(), E>> + Send`
+///   where `E: Into<ValidationErrors>`. Also allowed on the container, with `&Self`.
+/// - `nested_async`: validates the field with its own `AsyncValidate` implementation, merging its
+///   errors under the field.
+///
+/// The synchronous rules are checked first, then all the asynchronous rules are awaited
+/// concurrently. The fields must be `Sync`, so the future is `Send`.
+#[proc_macro_derive(AsyncValidate, attributes(validate))]
+pub fn derive_async_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    validate::derive(&input, Mode::Async)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive macro generating a `serde_validate::Sanitize` implementation from `#[sanitizer(...)]`
+/// attributes.
+///
+/// Field sanitizers, applied in the order they are declared:
+/// - `trim`, `lowercase`, `uppercase`, `collapse_whitespace` and `nfc`: call the function of
+///   `serde_validate::sanitizers` with the same name on a `String` field. `nfc` requires the
+///   `unicode` feature.
+/// - `custom = path::to_fn`: calls `fn(&mut T)`.
+/// - `nested`: sanitizes the field with its own `Sanitize` implementation.
+///
+/// Sanitizers on `Option<T>` fields are only applied when the value is present.
+///
+/// The container accepts `crate = "path::to::serde_validate"`, as `validate_deser` does.
+///
+/// The attribute is named `sanitizer` since `sanitize` is reserved by the compiler.
+///
+/// `validate_deser` reads the same attributes, so deriving `Sanitize` is only needed to sanitize
+/// values built otherwise.
+#[proc_macro_derive(Sanitize, attributes(sanitizer))]
+pub fn derive_sanitize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    sanitize::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
 struct HelperData {
@@ -159,7 +730,7 @@ fn named_def_full(
 ) -> proc_macro2::TokenStream {
     let generic_params = generics.params.to_token_stream();
     let where_clause = generics.where_clause.to_token_stream();
-    let fields = fields.to_token_stream();
+    let fields = helper_fields(fields);
     quote! {
         #name<#generic_params> #where_clause {
             #fields
@@ -167,6 +738,17 @@ fn named_def_full(
     }
 }
 
+/// Returns the fields of the helper type, without the attributes that only apply to the original.
+fn helper_fields(fields: &Punctuated<Field, Comma>) -> Punctuated<Field, Comma> {
+    let mut fields = fields.clone();
+    for field in fields.iter_mut() {
+        field
+            .attrs
+            .retain(|attr| !is_validate_attr(attr) && !is_sanitize_attr(attr));
+    }
+    fields
+}
+
 fn init_from_named(name: &Ident, fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let init_fields = fields.iter().map(|field| {
         let name = &field.ident;
@@ -207,7 +789,7 @@ fn unnamed_def_full(
 ) -> proc_macro2::TokenStream {
     let generic_params = generics.params.to_token_stream();
     let where_clause = generics.where_clause.to_token_stream();
-    let fields = fields.to_token_stream();
+    let fields = helper_fields(fields);
     quote! {
         #name<#generic_params>(#fields) #where_clause;
     }
@@ -264,11 +846,13 @@ fn enum_def(
     let where_clause = generics.where_clause.to_token_stream();
     let variants = variants.iter().map(|variant| {
         let name = &variant.ident;
-        match variant.fields {
+        let attrs = variant.attrs.iter().filter(|attr| !is_validate_attr(attr));
+        let def = match variant.fields {
             Fields::Named(ref fields) => named_def(name, &fields.named),
             Fields::Unnamed(ref fields) => unnamed_def(name, &fields.unnamed),
             Fields::Unit => quote! { #name },
-        }
+        };
+        quote! { #( #attrs )* #def }
     });
     quote! {
         enum #name<#generic_params> #where_clause {
@@ -302,7 +886,7 @@ fn init_from_enum(
 }
 
 fn named_def(name: &Ident, fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
-    let fields = fields.to_token_stream();
+    let fields = helper_fields(fields);
     quote! {
         #name {
             #fields
@@ -311,7 +895,7 @@ fn named_def(name: &Ident, fields: &Punctuated<Field, Comma>) -> proc_macro2::To
 }
 
 fn unnamed_def(name: &Ident, fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
-    let fields = fields.to_token_stream();
+    let fields = helper_fields(fields);
     quote! {
         #name(#fields)
     }
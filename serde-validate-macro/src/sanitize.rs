@@ -0,0 +1,155 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Implementation of `#[derive(Sanitize)]`, and of the field sanitizers shared with
+//! `validate_deser`.
+
+use crate::attrs::{SanitizeAttrs, Sanitizer};
+use crate::validate::{binding, is_option};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Path, Type};
+
+pub(crate) fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let krate = SanitizeAttrs::container_paths(&input.attrs)?.krate();
+
+    let body = match input.data {
+        Data::Struct(ref data) => {
+            let stmts = fields_stmts(&data.fields, &krate, |i, field| match field.ident {
+                Some(ref ident) => quote! { &mut self.#ident },
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! { &mut self.#index }
+                }
+            })?;
+            quote! { #( #stmts )* }
+        }
+        Data::Enum(ref data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let stmts = fields_stmts(&variant.fields, &krate, |i, _| {
+                        let binding = binding(i);
+                        quote! { #binding }
+                    })?;
+                    let mut bindings = Vec::new();
+                    for (i, field) in variant.fields.iter().enumerate() {
+                        if SanitizeAttrs::from_attrs(&field.attrs)?
+                            .sanitizers
+                            .is_empty()
+                        {
+                            continue;
+                        }
+                        let binding = binding(i);
+                        bindings.push(match field.ident {
+                            Some(ref ident) => quote! { #ident: #binding },
+                            None => {
+                                let index = syn::Index::from(i);
+                                quote! { #index: #binding }
+                            }
+                        });
+                    }
+                    Ok(quote! {
+                        Self::#variant_name { #( #bindings, )* .. } => {
+                            #( #stmts )*
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            if arms.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #( #arms )*
+                    }
+                }
+            }
+        }
+        Data::Union(ref data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "`Sanitize` cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #krate::Sanitize for #name #ty_generics #where_clause {
+            fn sanitize(&mut self) {
+                #body
+            }
+        }
+    })
+}
+
+/// Generates the sanitizers of every field, where `access` gives a mutable reference to the
+/// `i`-th field.
+fn fields_stmts(
+    fields: &Fields,
+    krate: &Path,
+    access: impl Fn(usize, &syn::Field) -> TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    let mut stmts = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let sanitizers = SanitizeAttrs::from_attrs(&field.attrs)?.sanitizers;
+        if let Some(stmt) = field_stmt(&sanitizers, &field.ty, &access(i, field), krate) {
+            stmts.push(stmt);
+        }
+    }
+    Ok(stmts)
+}
+
+/// Generates the statement applying `sanitizers` to `value`, a mutable reference to a field of
+/// type `ty`, or `None` if there are no sanitizers.
+///
+/// Sanitizers on `Option<T>` fields are only applied when the value is present.
+pub(crate) fn field_stmt(
+    sanitizers: &[Sanitizer],
+    ty: &Type,
+    value: &TokenStream,
+    krate: &Path,
+) -> Option<TokenStream> {
+    if sanitizers.is_empty() {
+        return None;
+    }
+    let calls = sanitizers.iter().map(|sanitizer| match sanitizer {
+        Sanitizer::Builtin(name) => quote! { #krate::sanitizers::#name(__value); },
+        Sanitizer::Custom(path) => quote! { #path(__value); },
+        Sanitizer::Nested => quote! { #krate::Sanitize::sanitize(__value); },
+    });
+    Some(if is_option(ty) {
+        quote! {
+            if let ::core::option::Option::Some(__value) = #value {
+                #( #calls )*
+            }
+        }
+    } else {
+        quote! {
+            {
+                let __value = #value;
+                #( #calls )*
+            }
+        }
+    })
+}
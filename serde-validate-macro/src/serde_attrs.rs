@@ -0,0 +1,138 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Handling of the `#[serde(...)]` attributes of the original type.
+//!
+//! The helper types are the ones actually deriving serde's traits, so they receive the serde
+//! attributes of the original type. The original type keeps them only if another macro still
+//! needs them, since `#[serde(...)]` is rejected on a type without a serde derive.
+//!
+//! Likewise, the `#[validate(...)]` and `#[sanitizer(...)]` attributes are only kept if a derive
+//! reads them, since `validate_deser` reads the field rules and sanitizers it applies while
+//! deserializing.
+
+use crate::attrs::{is_sanitize_attr, is_validate_attr};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Attribute, DeriveInput, Fields, Meta, Path};
+
+/// The attribute macros of this crate, which read the serde attributes they find.
+const SERDE_MACROS: [&str; 3] = ["validate_deser", "validate_ser", "validate_serde"];
+
+pub(crate) fn is_serde_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("serde")
+}
+
+/// Parses the items of every `#[serde(...)]` attribute in `attrs`.
+pub(crate) fn serde_metas(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| is_serde_attr(attr)) {
+        metas.extend(attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?);
+    }
+    Ok(metas)
+}
+
+/// Returns the derived trait paths of `attrs`, ignoring malformed derives.
+pub(crate) fn derived_paths(attrs: &[Attribute]) -> impl Iterator<Item = Path> + '_ {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+}
+
+/// Returns `true` if another macro on `input` still reads its serde attributes: a serde derive,
+/// or one of this crate's attribute macros that has not been expanded yet.
+fn serde_attrs_needed(input: &DeriveInput) -> bool {
+    let serde_derive = derived_paths(&input.attrs).any(|path| {
+        path.segments
+            .last()
+            .is_some_and(|s| s.ident == "Serialize" || s.ident == "Deserialize")
+    });
+    serde_derive || has_serde_macro(input)
+}
+
+/// Returns `true` if a validation derive or one of this crate's attribute macros on `input`
+/// reads its `#[validate(...)]` attributes.
+fn validate_attrs_needed(input: &DeriveInput) -> bool {
+    let validate_derive = derived_paths(&input.attrs).any(|path| {
+        path.segments
+            .last()
+            .is_some_and(|s| s.ident == "Validate" || s.ident == "AsyncValidate")
+    });
+    validate_derive || has_serde_macro(input)
+}
+
+/// Returns `true` if a `Sanitize` derive or one of this crate's attribute macros on `input` reads
+/// its `#[sanitizer(...)]` attributes.
+fn sanitize_attrs_needed(input: &DeriveInput) -> bool {
+    let sanitize_derive = derived_paths(&input.attrs)
+        .any(|path| path.segments.last().is_some_and(|s| s.ident == "Sanitize"));
+    sanitize_derive || has_serde_macro(input)
+}
+
+/// Returns `true` if one of this crate's attribute macros has not been expanded yet on `input`.
+fn has_serde_macro(input: &DeriveInput) -> bool {
+    input.attrs.iter().any(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|s| SERDE_MACROS.iter().any(|name| s.ident == name))
+    })
+}
+
+/// Returns the original type as it should be emitted, without the serde and validation
+/// attributes that nothing would read anymore.
+pub(crate) fn original(input: &DeriveInput) -> DeriveInput {
+    let mut input = input.clone();
+    if !serde_attrs_needed(&input) {
+        strip(&mut input, is_serde_attr);
+    }
+    if !validate_attrs_needed(&input) {
+        strip(&mut input, is_validate_attr);
+    }
+    if !sanitize_attrs_needed(&input) {
+        strip(&mut input, is_sanitize_attr);
+    }
+    input
+}
+
+/// Removes the attributes matching `is_stripped` from the container, variants and fields.
+fn strip(input: &mut DeriveInput, is_stripped: fn(&Attribute) -> bool) {
+    input.attrs.retain(|attr| !is_stripped(attr));
+    match input.data {
+        syn::Data::Struct(ref mut data) => strip_fields(&mut data.fields, is_stripped),
+        syn::Data::Enum(ref mut data) => {
+            for variant in data.variants.iter_mut() {
+                variant.attrs.retain(|attr| !is_stripped(attr));
+                strip_fields(&mut variant.fields, is_stripped);
+            }
+        }
+        syn::Data::Union(_) => {}
+    }
+}
+
+fn strip_fields(fields: &mut Fields, is_stripped: fn(&Attribute) -> bool) {
+    for field in fields.iter_mut() {
+        field.attrs.retain(|attr| !is_stripped(attr));
+    }
+}
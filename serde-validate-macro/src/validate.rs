@@ -0,0 +1,506 @@
+/*
+ * serde-validate-macro - A procedural macro that validates the deserialization of a struct
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Implementation of `#[derive(Validate)]` and `#[derive(AsyncValidate)]`.
+
+use crate::attrs::{ContainerAttrs, FieldAttrs, Rule};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Field, Fields, GenericArgument, Member, Path, PathArguments, Type};
+
+/// The checks being generated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// `Validate::validate`, which skips the asynchronous rules.
+    Sync,
+    /// `AsyncValidate`, which checks every rule and awaits the asynchronous ones concurrently.
+    Async,
+    /// `Validate::warnings`, which checks the synchronous rules marked `warn`.
+    Warnings,
+    /// `Validate::validate_group`, which also checks the synchronous rules of the group.
+    Group,
+}
+
+impl Mode {
+    /// Returns the rules checked in this mode, out of the `rules` and `warnings` of a field or
+    /// container.
+    fn rules<'a>(self, rules: &'a [Rule], warnings: &'a [Rule]) -> Vec<&'a Rule> {
+        match self {
+            Mode::Sync => rules
+                .iter()
+                .filter(|rule| !rule.is_async() && !rule.is_grouped())
+                .collect(),
+            Mode::Async => rules.iter().filter(|rule| !rule.is_grouped()).collect(),
+            Mode::Warnings => warnings.iter().filter(|rule| !rule.is_async()).collect(),
+            Mode::Group => rules.iter().filter(|rule| !rule.is_async()).collect(),
+        }
+    }
+}
+
+pub(crate) fn derive(input: &DeriveInput, mode: Mode) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let container = ContainerAttrs::from_attrs(&input.attrs)?;
+    if !matches!(input.data, Data::Struct(_)) {
+        let rules = container.rules.iter().chain(&container.warnings);
+        if let Some(field) = rules.flat_map(Rule::fields).next() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`required_if`, `required_unless` and `exactly_one_of` only apply to structs",
+            ));
+        }
+    }
+    let krate = container.paths.krate();
+    let body = checks(input, &container, mode, &krate)?;
+
+    Ok(match mode {
+        Mode::Sync => {
+            let warnings = checks(input, &container, Mode::Warnings, &krate)?.map(|body| {
+                quote! {
+                    fn warnings(&self) -> ::std::vec::Vec<#krate::Warning> {
+                        #body
+                        __errors
+                            .into_iter()
+                            .map(#krate::Warning::from)
+                            .collect()
+                    }
+                }
+            });
+            let validate_group = if has_groups(input, &container)? {
+                let body = checks(input, &container, Mode::Group, &krate)?;
+                Some(quote! {
+                    fn validate_group(
+                        &self,
+                        __group: #krate::Group,
+                    ) -> ::core::result::Result<(), Self::Error> {
+                        #body
+                        __errors.into_result()
+                    }
+                })
+            } else {
+                None
+            };
+            quote! {
+                impl #impl_generics #krate::Validate for #name #ty_generics #where_clause {
+                    type Error = #krate::ValidationErrors;
+
+                    fn validate(&self) -> ::core::result::Result<(), Self::Error> {
+                        #body
+                        __errors.into_result()
+                    }
+
+                    #validate_group
+
+                    #warnings
+                }
+            }
+        }
+        Mode::Async => quote! {
+            impl #impl_generics #krate::AsyncValidate for #name #ty_generics #where_clause {
+                type Error = #krate::ValidationErrors;
+
+                async fn validate_async(&self) -> ::core::result::Result<(), Self::Error> {
+                    #[allow(unused_mut)]
+                    let mut __checks: ::std::vec::Vec<#krate::__private::NestedCheck<'_>> =
+                        ::std::vec::Vec::new();
+                    #body
+                    for __result in #krate::__private::join_all(__checks).await {
+                        __errors.check(__result);
+                    }
+                    __errors.into_result()
+                }
+            }
+        },
+        Mode::Warnings | Mode::Group => {
+            unreachable!("warnings and groups are derived along with `Validate`")
+        }
+    })
+}
+
+/// Returns `true` if `Validate::validate_group` checks more than `Validate::validate`, because
+/// of grouped rules or of nested values that may have some.
+fn has_groups(input: &DeriveInput, container: &ContainerAttrs) -> syn::Result<bool> {
+    let fields: Vec<&Field> = match input.data {
+        Data::Struct(ref data) => data.fields.iter().collect(),
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let mut rules: Vec<Rule> = Vec::new();
+    for field in fields {
+        rules.extend(FieldAttrs::from_attrs(&field.attrs)?.rules);
+    }
+    Ok(rules
+        .iter()
+        .chain(&container.rules)
+        .any(|rule| rule.is_grouped() || matches!(rule.inner(), Rule::Nested)))
+}
+
+/// Generates the statements recording into `__errors` the broken rules checked in `mode`, or
+/// `None` if there is no such rule.
+fn checks(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    mode: Mode,
+    krate: &Path,
+) -> syn::Result<Option<TokenStream>> {
+    let mut any = false;
+    let field_checks = match input.data {
+        Data::Struct(ref data) => {
+            let checks = fields_checks(&data.fields, mode, krate, |i, field| match field.ident {
+                Some(ref ident) => quote! { &self.#ident },
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! { &self.#index }
+                }
+            })?;
+            any |= !checks.is_empty();
+            quote! { #( #checks )* }
+        }
+        Data::Enum(ref data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let checks = fields_checks(&variant.fields, mode, krate, |i, _| {
+                        let binding = binding(i);
+                        quote! { #binding }
+                    })?;
+                    any |= !checks.is_empty();
+                    let bindings =
+                        checked_fields(&variant.fields, mode)?
+                            .into_iter()
+                            .map(|(i, field)| {
+                                let binding = binding(i);
+                                match field.ident {
+                                    Some(ref ident) => quote! { #ident: #binding },
+                                    None => {
+                                        let index = syn::Index::from(i);
+                                        quote! { #index: #binding }
+                                    }
+                                }
+                            });
+                    Ok(quote! {
+                        Self::#variant_name { #( #bindings, )* .. } => {
+                            #( #checks )*
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            if arms.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #( #arms )*
+                    }
+                }
+            }
+        }
+        Data::Union(ref data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "`Validate` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let container_rules = mode.rules(&container.rules, &container.warnings);
+    any |= !container_rules.is_empty();
+    if mode == Mode::Warnings && !any {
+        return Ok(None);
+    }
+    let container_checks = container_rules
+        .into_iter()
+        .map(|rule| rule_stmt(rule, None, &quote! { self }, krate, mode));
+
+    Ok(Some(quote! {
+        #[allow(unused_mut)]
+        let mut __errors = #krate::ValidationErrors::new();
+        #field_checks
+        #( #container_checks )*
+    }))
+}
+
+pub(crate) fn binding(i: usize) -> Ident {
+    format_ident!("__field{}", i)
+}
+
+/// Returns the fields that declare at least one rule, with their position.
+fn checked_fields(fields: &Fields, mode: Mode) -> syn::Result<Vec<(usize, &Field)>> {
+    let mut checked = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+        if !mode.rules(&attrs.rules, &attrs.warnings).is_empty() {
+            checked.push((i, field));
+        }
+    }
+    Ok(checked)
+}
+
+/// Generates the checks of every field, where `access` gives a reference to the `i`-th field.
+fn fields_checks(
+    fields: &Fields,
+    mode: Mode,
+    krate: &Path,
+    access: impl Fn(usize, &Field) -> TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    let mut checks = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+        let rules = mode.rules(&attrs.rules, &attrs.warnings);
+        if rules.is_empty() {
+            continue;
+        }
+        if !is_option(&field.ty) && rules.iter().any(|rule| is_presence(rule)) {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "`required` only applies to `Option` fields",
+            ));
+        }
+        let segment = segment(i, field);
+        let check = field_check(
+            field,
+            &rules,
+            Some(&segment),
+            &access(i, field),
+            krate,
+            mode,
+        );
+        checks.push(quote! { { #check } });
+    }
+    Ok(checks)
+}
+
+/// Returns the path segment of the `i`-th field, its unraw name or its position.
+pub(crate) fn segment(i: usize, field: &Field) -> TokenStream {
+    match field.ident {
+        Some(ref ident) => {
+            let name = ident.unraw().to_string();
+            quote! { #name }
+        }
+        None => {
+            let index = Literal::usize_suffixed(i);
+            quote! { #index }
+        }
+    }
+}
+
+/// Returns the path segment of a field of the struct named by `member`.
+fn member_segment(member: &Member) -> TokenStream {
+    match member {
+        Member::Named(ident) => {
+            let name = ident.unraw().to_string();
+            quote! { #name }
+        }
+        Member::Unnamed(index) => {
+            let index = Literal::usize_suffixed(index.index as usize);
+            quote! { #index }
+        }
+    }
+}
+
+/// Returns `true` if `rule` checks whether an `Option` field is set, rather than its value.
+fn is_presence(rule: &Rule) -> bool {
+    matches!(rule.inner(), Rule::Required)
+}
+
+/// Generates the checks of `rules` on the field whose reference is given by `access`, recording
+/// the errors into `__errors` under `segment` if any.
+pub(crate) fn field_check(
+    field: &Field,
+    rules: &[&Rule],
+    segment: Option<&TokenStream>,
+    access: &TokenStream,
+    krate: &Path,
+    mode: Mode,
+) -> TokenStream {
+    let value = quote! { __value };
+    let rule_checks = rules
+        .iter()
+        .filter(|rule| !is_presence(rule))
+        .map(|rule| rule_stmt(rule, segment, &value, krate, mode));
+    if is_option(&field.ty) {
+        let presence_checks = rules
+            .iter()
+            .filter(|rule| is_presence(rule))
+            .map(|rule| rule_stmt(rule, segment, access, krate, mode));
+        quote! {
+            #( #presence_checks )*
+            if let ::core::option::Option::Some(__value) = #access {
+                #( #rule_checks )*
+            }
+        }
+    } else {
+        quote! {
+            let __value = #access;
+            #( #rule_checks )*
+        }
+    }
+}
+
+/// Generates the statement recording the errors of `rule` on `value`, under `segment` if any.
+///
+/// Synchronous rules record into `__errors` right away, asynchronous ones are pushed to
+/// `__checks` to be awaited concurrently. Grouped rules are only checked when `__group` is one
+/// of their groups, conditional ones when their condition on `self` holds.
+fn rule_stmt(
+    rule: &Rule,
+    segment: Option<&TokenStream>,
+    value: &TokenStream,
+    krate: &Path,
+    mode: Mode,
+) -> TokenStream {
+    let check = match rule {
+        Rule::Grouped { groups, rule } => {
+            let stmt = rule_stmt(rule, segment, value, krate, mode);
+            return quote! {
+                if ::core::matches!(#krate::Group::name(&__group), #( #groups )|*) {
+                    #stmt
+                }
+            };
+        }
+        Rule::When {
+            condition,
+            unless,
+            rule,
+        } => {
+            let stmt = rule_stmt(rule, segment, value, krate, mode);
+            let not = unless.then(|| quote! { ! });
+            return quote! {
+                if #not #condition(self) {
+                    #stmt
+                }
+            };
+        }
+        Rule::RequiredField(field) => {
+            let segment = member_segment(field);
+            let check = rule_check(rule, value, krate);
+            return quote! { __errors.nest(#segment, #check); };
+        }
+        Rule::Nested if mode == Mode::Group => {
+            quote! { #krate::Validate::validate_group(#value, __group) }
+        }
+        _ => rule_check(rule, value, krate),
+    };
+    let check = if rule.is_async() {
+        quote! { #check.await }
+    } else {
+        check
+    };
+    let record = match segment {
+        Some(segment) => quote! { __errors.nest(#segment, #check); },
+        None => quote! { __errors.check(#check); },
+    };
+    if rule.is_async() {
+        quote! {
+            __checks.push(::std::boxed::Box::pin(async move {
+                let mut __errors = #krate::ValidationErrors::new();
+                #record
+                __errors.into_result()
+            }));
+        }
+    } else {
+        record
+    }
+}
+
+/// Generates an expression checking `value`, of type `Result<(), impl Into<ValidationErrors>>`
+/// or a future resolving to it for asynchronous rules.
+pub(crate) fn rule_check(rule: &Rule, value: &TokenStream, krate: &Path) -> TokenStream {
+    match rule {
+        Rule::Range { min, max } => {
+            let min = optional(min.as_ref().map(|min| quote! { &(#min) }));
+            let max = optional(max.as_ref().map(|max| quote! { &(#max) }));
+            quote! { #krate::rules::range(#value, #min, #max) }
+        }
+        Rule::Length { min, max } => {
+            let min = optional(min.as_ref().map(|min| quote! { #min }));
+            let max = optional(max.as_ref().map(|max| quote! { #max }));
+            quote! { #krate::rules::length(#value, #min, #max) }
+        }
+        Rule::Custom(path) | Rule::Field(path) => quote! { #path(#value) },
+        Rule::Required => quote! { #krate::rules::required(#value) },
+        Rule::RequiredField(field) => quote! { #krate::rules::required(&(#value).#field) },
+        Rule::ExactlyOneOf(fields) => {
+            let fields = fields.iter().map(|field| {
+                let segment = member_segment(field);
+                quote! {
+                    (
+                        #krate::PathSegment::from(#segment),
+                        ::core::option::Option::is_some(&(#value).#field),
+                    )
+                }
+            });
+            quote! { #krate::rules::exactly_one_of(&[#( #fields ),*]) }
+        }
+        Rule::MaxItems(max) => {
+            let max = optional(Some(quote! { #max }));
+            quote! { #krate::rules::length(#value, ::core::option::Option::None, #max) }
+        }
+        Rule::Nested => quote! { #krate::Validate::validate(#value) },
+        Rule::NestedWarnings => quote! {
+            ::core::result::Result::Err::<(), #krate::ValidationErrors>(
+                ::core::iter::Iterator::collect(::core::iter::Iterator::map(
+                    ::core::iter::IntoIterator::into_iter(#krate::Validate::warnings(#value)),
+                    #krate::Warning::into_error,
+                )),
+            )
+        },
+        Rule::CustomAsync(path) => quote! { #path(#value) },
+        Rule::NestedAsync => quote! { #krate::AsyncValidate::validate_async(#value) },
+        Rule::Grouped { rule, .. } | Rule::When { rule, .. } => rule_check(rule, value, krate),
+    }
+}
+
+fn optional(value: Option<TokenStream>) -> TokenStream {
+    match value {
+        Some(value) => quote! { ::core::option::Option::Some(#value) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
+
+/// Returns `true` if `ty` is spelled as an `Option<T>`, in which case rules apply to the
+/// contained value only when it is present.
+pub(crate) fn is_option(ty: &Type) -> bool {
+    option_inner(ty).is_some()
+}
+
+/// Returns the `T` of `ty` if it is spelled as an `Option<T>`.
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let segment = ty.path.segments.last().filter(|_| ty.qself.is_none())?;
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref args) if segment.ident == "Option" => {
+            match args.args.first() {
+                Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Asynchronous validation, for rules that need I/O.
+
+use crate::ValidationErrors;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The `AsyncValidate` trait defines validation that needs to await, such as uniqueness lookups
+/// or calls to a policy service.
+///
+/// Since serde is synchronous, asynchronous validation cannot run inside `Deserialize`. Deserialize
+/// a [`Pending`] value instead, and validate it afterwards.
+///
+/// The futures are `Send`, so they can be spawned on multi-threaded executors.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::AsyncValidate;
+/// use std::future::Future;
+///
+/// struct Username(String);
+///
+/// async fn is_taken(name: &str) -> bool {
+///     name == "admin"
+/// }
+///
+/// impl AsyncValidate for Username {
+///     type Error = String;
+///
+///     fn validate_async(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+///         async move {
+///             if is_taken(&self.0).await {
+///                 Err(format!("{} is taken", self.0))
+///             } else {
+///                 Ok(())
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub trait AsyncValidate: Sized {
+    /// The error type returned by the `validate_async` method.
+    type Error;
+
+    /// Validates the instance, resolving to `Ok(())` if valid, or an `Error` otherwise.
+    fn validate_async(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Consumes the instance, validating it and resolving to the instance itself if valid.
+    fn validated_async(self) -> impl Future<Output = Result<Self, Self::Error>> + Send
+    where
+        Self: Send,
+    {
+        async move { self.validate_async().await.map(|_| self) }
+    }
+}
+
+impl<T: AsyncValidate + Sync> AsyncValidate for Option<T> {
+    type Error = T::Error;
+
+    async fn validate_async(&self) -> Result<(), Self::Error> {
+        match self {
+            Some(value) => value.validate_async().await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: AsyncValidate + Sync> AsyncValidate for Box<T> {
+    type Error = T::Error;
+
+    async fn validate_async(&self) -> Result<(), Self::Error> {
+        (**self).validate_async().await
+    }
+}
+
+impl<T> AsyncValidate for Vec<T>
+where
+    T: AsyncValidate + Sync,
+    T::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    /// Validates all the elements concurrently.
+    async fn validate_async(&self) -> Result<(), Self::Error> {
+        let checks = self
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                Box::pin(async move {
+                    let mut errors = ValidationErrors::new();
+                    errors.nest(i, value.validate_async().await);
+                    errors.into_result()
+                }) as NestedCheck<'_>
+            })
+            .collect();
+        let mut errors = ValidationErrors::new();
+        for result in join_all(checks).await {
+            errors.check(result);
+        }
+        errors.into_result()
+    }
+}
+
+/// A value that was deserialized but not validated yet.
+///
+/// This is the first phase of asynchronous validated deserialization: `Pending<T>` deserializes
+/// like `T`, and [`Pending::validate`] completes the second phase.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_validate::{AsyncValidate, Pending};
+/// use std::future::Future;
+///
+/// #[derive(Deserialize)]
+/// struct Username(String);
+///
+/// impl AsyncValidate for Username {
+///     type Error = String;
+///
+///     fn validate_async(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+///         async move { Ok(()) }
+///     }
+/// }
+///
+/// async fn parse(input: &str) -> Result<Username, String> {
+///     let pending: Pending<Username> = serde_json::from_str(input).map_err(|e| e.to_string())?;
+///     pending.validate().await
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pending<T>(T);
+
+impl<T> Pending<T> {
+    /// Wraps a value that still needs to be validated.
+    pub fn new(value: T) -> Self {
+        Pending(value)
+    }
+
+    /// Returns a reference to the unvalidated value.
+    pub fn get_unvalidated(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the value without validating it.
+    pub fn into_unvalidated(self) -> T {
+        self.0
+    }
+}
+
+impl<T: AsyncValidate + Send> Pending<T> {
+    /// Validates the value, resolving to it if valid.
+    pub async fn validate(self) -> Result<T, T::Error> {
+        self.0.validated_async().await
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Pending<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Pending)
+    }
+}
+
+/// The asynchronous check of a nested value, resolving to its errors.
+#[doc(hidden)]
+pub type NestedCheck<'a> = Pin<Box<dyn Future<Output = Result<(), ValidationErrors>> + Send + 'a>>;
+
+/// Polls all the futures concurrently, resolving to their outputs in order.
+#[doc(hidden)]
+pub fn join_all<F: Future + Unpin>(futures: Vec<F>) -> JoinAll<F> {
+    JoinAll {
+        outputs: futures.iter().map(|_| None).collect(),
+        futures: futures.into_iter().map(Some).collect(),
+    }
+}
+
+#[doc(hidden)]
+pub struct JoinAll<F: Future> {
+    futures: Vec<Option<F>>,
+    outputs: Vec<Option<F::Output>>,
+}
+
+// The futures are `Unpin` and the outputs are never pinned.
+impl<F: Future> Unpin for JoinAll<F> {}
+
+impl<F: Future + Unpin> Future for JoinAll<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut done = true;
+        for (future, output) in this.futures.iter_mut().zip(this.outputs.iter_mut()) {
+            if let Some(pending) = future {
+                match Pin::new(pending).poll(cx) {
+                    Poll::Ready(value) => {
+                        *output = Some(value);
+                        *future = None;
+                    }
+                    Poll::Pending => done = false,
+                }
+            }
+        }
+        if done {
+            Poll::Ready(this.outputs.iter_mut().filter_map(Option::take).collect())
+        } else {
+            Poll::Pending
+        }
+    }
+}
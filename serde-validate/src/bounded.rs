@@ -0,0 +1,258 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Strings and vectors with a maximum length checked while they are deserialized.
+
+#[cfg(feature = "serde")]
+use crate::limit::{check_seq_end, check_size_hint};
+use crate::rules::length;
+use crate::ValidationError;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+/// A string of at most `MAX` characters.
+///
+/// Its `Deserialize` impl rejects longer strings before copying them, so an oversize string is
+/// never allocated by the value itself.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::BoundedString;
+///
+/// let name: BoundedString<5> = serde_json::from_str(r#""Ann""#).unwrap();
+/// assert_eq!(&*name, "Ann");
+///
+/// let err = serde_json::from_str::<BoundedString<5>>(r#""Annabelle""#).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid length 9, expected a string of at most 5 characters at line 1 column 11"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedString<const MAX: usize>(String);
+
+impl<const MAX: usize> BoundedString<MAX> {
+    /// Checks the length of `value`, wrapping it if it has at most `MAX` characters.
+    pub fn new(value: impl Into<String>) -> Result<Self, ValidationError> {
+        let value = value.into();
+        length(&value, None, Some(MAX))?;
+        Ok(BoundedString(value))
+    }
+
+    /// Returns the string.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Deref for BoundedString<MAX> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> AsRef<str> for BoundedString<MAX> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> Display for BoundedString<MAX> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A vector of at most `MAX` elements.
+///
+/// Its `Deserialize` impl rejects a sequence as soon as it announces or yields more than `MAX`
+/// elements, without deserializing the extra ones.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::BoundedVec;
+///
+/// let tags: BoundedVec<String, 2> = serde_json::from_str(r#"["a", "b"]"#).unwrap();
+/// assert_eq!(tags.len(), 2);
+///
+/// let err = serde_json::from_str::<BoundedVec<String, 2>>(r#"["a", "b", "c"]"#).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "too many items, expected at most 2 at line 1 column 15"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedVec<T, const MAX: usize>(Vec<T>);
+
+impl<T, const MAX: usize> BoundedVec<T, MAX> {
+    /// Checks the length of `values`, wrapping them if there are at most `MAX`.
+    pub fn new(values: Vec<T>) -> Result<Self, ValidationError> {
+        length(values.as_slice(), None, Some(MAX))?;
+        Ok(BoundedVec(values))
+    }
+
+    /// Returns the elements.
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T, const MAX: usize> Deref for BoundedVec<T, MAX> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T, const MAX: usize> AsRef<[T]> for BoundedVec<T, MAX> {
+    fn as_ref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T, const MAX: usize> IntoIterator for BoundedVec<T, MAX> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T, const MAX: usize> IntoIterator for &'a BoundedVec<T, MAX> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const MAX: usize> serde::Serialize for BoundedString<MAX> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct StringVisitor<const MAX: usize>;
+
+#[cfg(feature = "serde")]
+impl<const MAX: usize> StringVisitor<MAX> {
+    fn check<E: serde::de::Error>(&self, value: &str) -> Result<(), E> {
+        // A string of at most `MAX` bytes has at most `MAX` characters.
+        if value.len() > MAX && value.chars().nth(MAX).is_some() {
+            Err(E::invalid_length(value.chars().count(), self))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const MAX: usize> serde::de::Visitor<'_> for StringVisitor<MAX> {
+    type Value = BoundedString<MAX>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a string of at most {MAX} characters")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.check(value)?;
+        Ok(BoundedString(value.to_owned()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, value: String) -> Result<Self::Value, E> {
+        self.check(&value)?;
+        Ok(BoundedString(value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const MAX: usize> serde::Deserialize<'de> for BoundedString<MAX> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_string(StringVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const MAX: usize> serde::Serialize for BoundedVec<T, MAX> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct VecVisitor<T, const MAX: usize>(std::marker::PhantomData<fn() -> T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T, const MAX: usize> serde::de::Visitor<'de> for VecVisitor<T, MAX>
+where
+    T: serde::Deserialize<'de>,
+{
+    type Value = BoundedVec<T, MAX>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of at most {MAX} elements")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        check_size_hint(seq.size_hint(), MAX)?;
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while values.len() < MAX {
+            match seq.next_element()? {
+                Some(value) => values.push(value),
+                None => return Ok(BoundedVec(values)),
+            }
+        }
+        check_seq_end(&mut seq, MAX)?;
+        Ok(BoundedVec(values))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const MAX: usize> serde::Deserialize<'de> for BoundedVec<T, MAX>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(VecVisitor(std::marker::PhantomData))
+    }
+}
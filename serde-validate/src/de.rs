@@ -0,0 +1,842 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Utilities for validated deserialization.
+
+use crate::{
+    Group, PathSegment, RepairAction, Validate, ValidateWith, ValidationError, ValidationErrors,
+    Warning,
+};
+use serde::de::{DeserializeSeed, Expected, Unexpected};
+use serde::{Deserialize, Deserializer};
+use std::any::{type_name, Any};
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+pub use crate::limit::Limited;
+
+/// The error returned by [`from_deserializer`].
+///
+/// It tells apart input that could not be parsed from input that was parsed but broke a rule.
+#[derive(Debug)]
+pub enum DeserializeError<D, V> {
+    /// The input could not be deserialized.
+    Deserialize(D),
+    /// The input was deserialized but failed validation.
+    Validate(V),
+}
+
+impl<D: Display, V: Display> Display for DeserializeError<D, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::Deserialize(err) => write!(f, "{err}"),
+            DeserializeError::Validate(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl<D: Debug + Display, V: Debug + Display> std::error::Error for DeserializeError<D, V> {}
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static TARGET: Cell<Option<Target>> = const { Cell::new(None) };
+    static CAPTURED: RefCell<Option<Box<dyn Any>>> = const { RefCell::new(None) };
+    static UNVALIDATED: Cell<usize> = const { Cell::new(0) };
+    static REPAIRS: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+}
+
+/// The value whose own validation is left to the caller of its `Deserialize` impl, such as
+/// [`from_deserializer`], so its errors are reported as they are.
+#[derive(Clone, Copy)]
+struct Target {
+    /// The depth of the frame of the value, see [`Frame`].
+    depth: usize,
+    /// The name of the type of the value, telling its frame apart from the frame of any value it
+    /// is made of.
+    type_name: &'static str,
+    /// Whether the frame of the value was entered.
+    entered: bool,
+    /// Whether the frame left its validation to the caller, rather than validating as it went.
+    deferred: bool,
+    /// Whether the caller may repair the value, so its field checks are left to it too rather
+    /// than fail before the value is complete.
+    repairable: bool,
+    /// The group the frame validates for, from `#[validate_deser(group = "...")]`.
+    group: Option<Group>,
+}
+
+/// The deserialization of a value that validates itself, such as in a `validate_deser` impl.
+///
+/// Frames are entered as values nest, so the frame of the target of [`from_deserializer`] can be
+/// told apart from the frames of the values it is made of.
+#[doc(hidden)]
+pub struct Frame {
+    _private: (),
+}
+
+impl Frame {
+    /// Enters the frame of a `T` validated once deserialized, for `group` if any.
+    ///
+    /// As the target, its validation is left to the caller, see [`skips_validation`].
+    pub fn enter<T: ?Sized>(group: Option<Group>) -> Self {
+        Frame::start::<T>(group, true)
+    }
+
+    /// Enters the frame of a `T` validated as it is deserialized, such as a streamed sequence.
+    ///
+    /// As the target, its errors are reported to the caller by [`map_validation_error`].
+    pub(crate) fn validating<T: ?Sized>() -> Self {
+        Frame::start::<T>(None, false)
+    }
+
+    fn start<T: ?Sized>(group: Option<Group>, deferred: bool) -> Self {
+        let depth = DEPTH.with(|d| {
+            d.set(d.get() + 1);
+            d.get()
+        });
+        TARGET.with(|t| match t.get() {
+            Some(target)
+                if !target.entered
+                    && target.depth == depth
+                    && unwrapped(target.type_name) == type_name::<T>() =>
+            {
+                t.set(Some(Target {
+                    entered: true,
+                    deferred,
+                    group,
+                    ..target
+                }));
+            }
+            _ => {}
+        });
+        Frame { _private: () }
+    }
+}
+
+/// Strips the transparent wrappers off a type name, such as `Option` and `Box`. They deserialize
+/// the value they wrap without a frame of their own, so a target `Option<T>` is entered by the
+/// frame of its `T`.
+fn unwrapped(mut name: &'static str) -> &'static str {
+    const WRAPPERS: [&str; 4] = [
+        "core::option::Option<",
+        "alloc::boxed::Box<",
+        "alloc::rc::Rc<",
+        "alloc::sync::Arc<",
+    ];
+    while let Some(inner) = WRAPPERS.iter().find_map(|wrapper| {
+        name.strip_prefix(wrapper)
+            .and_then(|inner| inner.strip_suffix('>'))
+    }) {
+        name = inner;
+    }
+    name
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
+/// Returns the target if the innermost frame is its frame.
+fn innermost_target() -> Option<Target> {
+    let depth = DEPTH.with(Cell::get);
+    TARGET
+        .with(Cell::get)
+        .filter(|target| target.entered && target.depth == depth)
+}
+
+/// Makes the `T` about to be deserialized the target for as long as it lives.
+struct TargetGuard {
+    previous: Option<Target>,
+    captured: Option<Box<dyn Any>>,
+}
+
+impl TargetGuard {
+    fn start<T: ?Sized>() -> Self {
+        TargetGuard::with::<T>(false)
+    }
+
+    /// Makes the `T` the target of a caller that may repair it, see [`skips_field_checks`].
+    fn repairable<T: ?Sized>() -> Self {
+        TargetGuard::with::<T>(true)
+    }
+
+    fn with<T: ?Sized>(repairable: bool) -> Self {
+        let target = Target {
+            depth: DEPTH.with(Cell::get) + 1,
+            type_name: type_name::<T>(),
+            entered: false,
+            deferred: false,
+            repairable,
+            group: None,
+        };
+        TargetGuard {
+            previous: TARGET.with(|t| t.replace(Some(target))),
+            captured: CAPTURED.with(|c| c.borrow_mut().take()),
+        }
+    }
+
+    /// Returns `true` if the frame of the target validated it as it went, which it does not for
+    /// trusted input.
+    fn checked(&self) -> bool {
+        !is_unvalidated()
+            && TARGET
+                .with(Cell::get)
+                .is_some_and(|target| target.entered && !target.deferred)
+    }
+
+    /// Validates `value` unless its frame validated it as it went.
+    ///
+    /// A value whose frame left its validation to the caller is validated as the frame would
+    /// have, for its group if any. A value without a frame, such as a plain `Deserialize` type,
+    /// is validated too, since nothing validated it while deserializing.
+    fn validate<T: Validate>(&self, value: &T) -> Result<(), T::Error> {
+        if self.checked() {
+            return Ok(());
+        }
+        match TARGET.with(Cell::get).and_then(|target| target.group) {
+            Some(group) => value.validate_group(group),
+            None => value.validate(),
+        }
+    }
+
+    /// Classifies `err` as a validation failure if the target reported a `V` while failing.
+    fn classify<D, V: 'static>(&self, err: D) -> DeserializeError<D, V> {
+        match CAPTURED
+            .with(|c| c.borrow_mut().take())
+            .map(|c| c.downcast::<V>())
+        {
+            Some(Ok(captured)) => DeserializeError::Validate(*captured),
+            _ => DeserializeError::Deserialize(err),
+        }
+    }
+}
+
+impl Drop for TargetGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        TARGET.with(|t| t.set(previous));
+        let captured = self.captured.take();
+        CAPTURED.with(|c| *c.borrow_mut() = captured);
+    }
+}
+
+/// Marks the current thread as deserializing trusted input for as long as it lives.
+struct UnvalidatedGuard;
+
+impl UnvalidatedGuard {
+    fn start() -> Self {
+        UNVALIDATED.with(|c| c.set(c.get() + 1));
+        UnvalidatedGuard
+    }
+}
+
+impl Drop for UnvalidatedGuard {
+    fn drop(&mut self) {
+        UNVALIDATED.with(|c| c.set(c.get() - 1));
+    }
+}
+
+/// Returns `true` if validation is skipped because the input is trusted or the value is
+/// validated afterwards as the target.
+#[doc(hidden)]
+pub fn skips_validation() -> bool {
+    is_unvalidated() || innermost_target().is_some_and(|target| target.deferred)
+}
+
+/// Returns `true` if the field checks are skipped because the input is trusted or the value may
+/// be repaired afterwards as the target.
+///
+/// The field checks of any other target still run as the fields are deserialized, reporting
+/// their errors to the caller through [`map_validation_error`].
+#[doc(hidden)]
+pub fn skips_field_checks() -> bool {
+    is_unvalidated() || innermost_target().is_some_and(|target| target.repairable)
+}
+
+/// Returns `true` if the input is trusted, so nothing is validated.
+pub(crate) fn is_unvalidated() -> bool {
+    UNVALIDATED.with(|c| c.get()) > 0
+}
+
+/// Returns `true` if the `on_invalid` repairs are skipped because the input is trusted.
+#[doc(hidden)]
+pub fn skips_repairs() -> bool {
+    is_unvalidated()
+}
+
+/// Runs `deserialize` with the `T` it deserializes as the target, for a value the caller validates
+/// afterwards. Only the checks of the `T` itself are left out, the values it is made of are still
+/// checked, and the `on_invalid` repairs still apply.
+#[doc(hidden)]
+pub fn defer_validation<T: ?Sized, R>(deserialize: impl FnOnce() -> R) -> R {
+    let _target = TargetGuard::repairable::<T>();
+    deserialize()
+}
+
+/// Collects the repairs made on the current thread, as warnings, for as long as it lives.
+struct RepairGuard {
+    previous: Option<Vec<Warning>>,
+}
+
+impl RepairGuard {
+    fn start() -> Self {
+        let previous = REPAIRS.with(|r| r.replace(Some(Vec::new())));
+        RepairGuard { previous }
+    }
+
+    fn finish(self) -> Vec<Warning> {
+        REPAIRS.with(|r| r.borrow_mut().take()).unwrap_or_default()
+    }
+}
+
+impl Drop for RepairGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        REPAIRS.with(|r| *r.borrow_mut() = previous);
+    }
+}
+
+/// Records a repair of the value at `segment`, which broke the rules of `errors`, if the caller
+/// collects them. See [`RepairAction`] for how it is reported.
+#[doc(hidden)]
+pub fn record_repair(
+    segment: impl Into<PathSegment>,
+    action: RepairAction,
+    errors: ValidationErrors,
+) {
+    REPAIRS.with(|r| {
+        if let Some(repairs) = r.borrow_mut().as_mut() {
+            let warning = Warning::new("repaired")
+                .with_message(format!("{action} ({errors})"))
+                .with_param("action", action.policy())
+                .at(segment);
+            repairs.push(warning);
+        }
+    });
+}
+
+/// Deserializes the element of a collection at `segment` with `next`, resolving to `None` once
+/// there are no elements left and to `Some(None)` if the element is invalid.
+///
+/// The element is the target, so it is dropped on its own errors, recording a repair, rather than
+/// failing deserialization. The values it is made of are still checked as it is deserialized, so
+/// their errors fail it: the input is already consumed past them by the time they are reported.
+pub(crate) fn next_valid<T, E>(
+    segment: impl Into<PathSegment>,
+    next: impl FnOnce() -> Result<Option<T>, E>,
+) -> Result<Option<Option<T>>, E>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    let start = repairs_len();
+    let target = TargetGuard::repairable::<T>();
+    let Some(value) = next()? else {
+        return Ok(None);
+    };
+    let result = target.validate(&value);
+    drop(target);
+    match result {
+        Ok(()) => {
+            nest_repairs(start, segment);
+            Ok(Some(Some(value)))
+        }
+        Err(err) => {
+            // The repairs made inside a dropped element are moot.
+            REPAIRS.with(|r| {
+                r.borrow_mut()
+                    .as_mut()
+                    .map(|repairs| repairs.truncate(start))
+            });
+            record_repair(segment, RepairAction::Skip, err.into());
+            Ok(Some(None))
+        }
+    }
+}
+
+/// Deserializes the element of a streamed collection at `segment` with `next`, resolving to `None`
+/// once there are no elements left, and validates it once.
+///
+/// The element is the target, so a `validate_deser` element leaves its validation to this
+/// function, and the errors of its field checks are reported under `segment` too.
+pub(crate) fn next_checked<T, E>(
+    segment: impl Into<PathSegment>,
+    next: impl FnOnce() -> Result<Option<T>, E>,
+) -> Result<Option<T>, E>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+    E: serde::de::Error,
+{
+    if is_unvalidated() {
+        return next();
+    }
+    let target = TargetGuard::start::<T>();
+    let mut errors = ValidationErrors::new();
+    match next() {
+        Ok(Some(value)) => {
+            errors.nest(segment, target.validate(&value));
+            if errors.is_empty() {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None) => return Ok(None),
+        Err(err) => match target.classify::<E, ValidationErrors>(err) {
+            DeserializeError::Validate(field_errors) => errors.extend_at(segment, field_errors),
+            DeserializeError::Deserialize(err) => return Err(err),
+        },
+    }
+    // The errors are captured for the collection once the element is no longer the target.
+    drop(target);
+    Err(map_validation_error(errors, custom))
+}
+
+/// Returns the number of repairs recorded so far, to be passed to [`nest_repairs`].
+#[doc(hidden)]
+pub fn repairs_len() -> usize {
+    REPAIRS.with(|r| r.borrow().as_ref().map_or(0, Vec::len))
+}
+
+/// Prepends `segment` to the repairs recorded since there were `start` of them.
+#[doc(hidden)]
+pub fn nest_repairs(start: usize, segment: impl Into<PathSegment>) {
+    REPAIRS.with(|r| {
+        if let Some(repairs) = r.borrow_mut().as_mut() {
+            let segment = segment.into();
+            for repair in repairs.iter_mut().skip(start) {
+                repair.path_mut().prepend(segment.clone());
+            }
+        }
+    });
+}
+
+/// Deserializes a `T` validated once, for a [`Validated`](crate::Validated) wrapper.
+///
+/// The `T` is the target, so a `validate_deser` impl leaves its validation to this function
+/// rather than validating twice. The `T` is validated even from trusted input, since a
+/// `Validated` wrapper cannot hold an unvalidated value.
+pub(crate) fn deserialize_validated<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Validate + Deserialize<'de>,
+    T::Error: Display,
+    D: Deserializer<'de>,
+{
+    let target = TargetGuard::start::<T>();
+    let value = T::deserialize(deserializer)?;
+    if !target.checked() {
+        value.validate().map_err(|err| custom(&err))?;
+    }
+    Ok(value)
+}
+
+/// Deserializes a `T` from trusted input, without validating it.
+///
+/// This is meant for input known to be valid, such as a snapshot written by the same program
+/// after validating it, where validating again would only cost time. Every check made while
+/// deserializing the value is skipped: the `validate_deser` and `validate_serde` impls, including
+/// their field checks, and the [`ValidatedSeq`](crate::ValidatedSeq) and
+/// [`ValidatedMap`](crate::ValidatedMap) wrappers, at any depth. Size limits such as
+/// [`BoundedVec`](crate::BoundedVec) or `max_items` still apply, and so does the validation of a
+/// [`Validated`](crate::Validated) wrapper, which cannot hold an unvalidated value.
+///
+/// Hand-written `Deserialize` impls calling `validate` themselves are not affected.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::de::deserialize_unvalidated;
+/// use serde_validate::{validate_deser, Validate};
+///
+/// #[validate_deser]
+/// #[derive(Validate)]
+/// struct Order {
+///     #[validate(range(min = 1))]
+///     quantity: u32,
+/// }
+///
+/// let input = r#"{ "quantity": 0 }"#;
+/// assert!(serde_json::from_str::<Order>(input).is_err());
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(input);
+/// let order: Order = deserialize_unvalidated(&mut deserializer).unwrap();
+/// assert_eq!(order.quantity, 0);
+/// ```
+pub fn deserialize_unvalidated<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let _guard = UnvalidatedGuard::start();
+    T::deserialize(deserializer)
+}
+
+/// A `T` deserialized from trusted input, without validating it.
+///
+/// Deserializing an `Unchecked<T>` deserializes a `T` with [`deserialize_unvalidated`], so it can
+/// be used with any deserialization entry point, such as `serde_json::from_str`. It serializes
+/// as `T`.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::de::Unchecked;
+/// use serde_validate::{validate_deser, Validate};
+///
+/// #[validate_deser]
+/// #[derive(Validate)]
+/// struct Order {
+///     #[validate(range(min = 1))]
+///     quantity: u32,
+/// }
+///
+/// let Unchecked(orders) =
+///     serde_json::from_str::<Unchecked<Vec<Order>>>(r#"[{ "quantity": 0 }]"#).unwrap();
+/// assert_eq!(orders[0].quantity, 0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unchecked<T>(pub T);
+
+impl<T> Unchecked<T> {
+    /// Returns the unvalidated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Unchecked<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_unvalidated(deserializer).map(Unchecked)
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Unchecked<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializes and validates a `T`, keeping the typed validation error.
+///
+/// The `Deserialize` impl generated by `validate_deser` can only report validation failures
+/// through `serde::de::Error::custom`, which turns them into strings. This function validates the
+/// `T` itself instead, returning the original `T::Error`, so callers can tell a parse failure
+/// from a broken rule.
+///
+/// A `T` with a plain `Deserialize` impl is validated the same way once deserialized. The field
+/// checks of a `validate_deser` impl still fail as soon as their field is deserialized, and are
+/// reported as [`DeserializeError::Validate`] when `T::Error` is `ValidationErrors`.
+///
+/// Only the rules of the `T` are reported as [`DeserializeError::Validate`]. A nested value
+/// rejected by its own `Deserialize` impl is reported as [`DeserializeError::Deserialize`], since
+/// its errors are relative to it rather than to the `T`.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{from_deserializer, validate_deser, DeserializeError, Validate};
+///
+/// #[validate_deser]
+/// struct Positive(i32);
+///
+/// impl Validate for Positive {
+///     type Error = String;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.0 > 0 {
+///             Ok(())
+///         } else {
+///             Err("must be positive".into())
+///         }
+///     }
+/// }
+///
+/// let result = from_deserializer::<Positive, _>(&mut serde_json::Deserializer::from_str("-1"));
+/// assert!(matches!(result, Err(DeserializeError::Validate(e)) if e == "must be positive"));
+///
+/// let result = from_deserializer::<Positive, _>(&mut serde_json::Deserializer::from_str("\"1\""));
+/// assert!(matches!(result, Err(DeserializeError::Deserialize(_))));
+/// ```
+pub fn from_deserializer<'de, T, D>(
+    deserializer: D,
+) -> Result<T, DeserializeError<D::Error, T::Error>>
+where
+    T: Deserialize<'de> + Validate,
+    T::Error: 'static,
+    D: Deserializer<'de>,
+{
+    let target = TargetGuard::start::<T>();
+    let value = T::deserialize(deserializer).map_err(|err| target.classify(err))?;
+    target
+        .validate(&value)
+        .map_err(DeserializeError::Validate)?;
+    Ok(value)
+}
+
+/// The warnings of a deserialized value.
+pub type Warnings = Vec<Warning>;
+
+/// Deserializes and validates a `T`, returning its warnings next to it.
+///
+/// Warnings, such as the `#[validate(..., warn)]` rules of `#[derive(Validate)]`, never fail
+/// deserialization. They are collected with [`Validate::warnings`] once the value is valid.
+///
+/// They are preceded by the repairs made on the way, in the order they were made. Fields with an
+/// `#[validate(on_invalid = "...")]` policy are repaired instead of failing deserialization:
+/// `"default"` replaces an invalid value by its default, `"skip"` drops the invalid elements of a
+/// collection and `"clamp"` clamps a value into its `range`. Each repair is reported as a
+/// [`RepairAction`] warning located at the value. The repairs of nested values are located from
+/// `T` as long as their field goes through a `validate_deser` impl with a `nested` rule; otherwise
+/// they are located from the closest such field. Every other entry point repairs the value the
+/// same way, without reporting it.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{from_deserializer_with_warnings, validate_deser, Validate};
+///
+/// #[validate_deser]
+/// #[derive(Validate)]
+/// struct Config {
+///     #[validate(range(min = 1))]
+///     #[validate(range(max = 64), warn)]
+///     workers: u32,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{ "workers": 100 }"#);
+/// let (config, warnings) =
+///     from_deserializer_with_warnings::<Config, _>(&mut deserializer).unwrap();
+/// assert_eq!(config.workers, 100);
+/// assert_eq!(warnings[0].to_string(), "workers: must be at most 64");
+/// ```
+pub fn from_deserializer_with_warnings<'de, T, D>(
+    deserializer: D,
+) -> Result<(T, Warnings), DeserializeError<D::Error, T::Error>>
+where
+    T: Deserialize<'de> + Validate,
+    T::Error: 'static,
+    D: Deserializer<'de>,
+{
+    let repairs = RepairGuard::start();
+    let value: T = from_deserializer(deserializer)?;
+    let mut warnings = repairs.finish();
+    warnings.extend(value.warnings());
+    Ok((value, warnings))
+}
+
+/// Deserializes and validates a `T` against `ctx`, keeping the typed validation error.
+///
+/// This is the [`ValidateWith`] counterpart of [`from_deserializer`].
+pub fn from_deserializer_with<'de, T, Ctx, D>(
+    deserializer: D,
+    ctx: &Ctx,
+) -> Result<T, DeserializeError<D::Error, <T as ValidateWith<Ctx>>::Error>>
+where
+    T: DeserializeWith<'de, Ctx> + ValidateWith<Ctx>,
+    <T as ValidateWith<Ctx>>::Error: 'static,
+    Ctx: ?Sized,
+    D: Deserializer<'de>,
+{
+    let target = TargetGuard::start::<T>();
+    let value = T::deserialize_with(deserializer, ctx).map_err(|err| target.classify(err))?;
+    if !target.checked() {
+        value
+            .validate_with(ctx)
+            .map_err(DeserializeError::Validate)?;
+    }
+    Ok(value)
+}
+
+/// A data structure that can be deserialized given some context, usually to validate it.
+///
+/// This trait is implemented by `#[validate_deser(context = Ctx)]`.
+pub trait DeserializeWith<'de, Ctx: ?Sized>: Sized {
+    /// Deserializes the value from `deserializer`, using `ctx`.
+    fn deserialize_with<D>(deserializer: D, ctx: &Ctx) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// A [`DeserializeSeed`] carrying a context through deserialization.
+///
+/// # Example
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use serde_validate::de::WithContext;
+/// use serde_validate::{validate_deser, ValidateWith};
+///
+/// struct Limits {
+///     max_items: usize,
+/// }
+///
+/// #[validate_deser(context = Limits)]
+/// struct Cart {
+///     items: Vec<String>,
+/// }
+///
+/// impl ValidateWith<Limits> for Cart {
+///     type Error = String;
+///
+///     fn validate_with(&self, limits: &Limits) -> Result<(), Self::Error> {
+///         if self.items.len() <= limits.max_items {
+///             Ok(())
+///         } else {
+///             Err("too many items".into())
+///         }
+///     }
+/// }
+///
+/// let limits = Limits { max_items: 1 };
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{ "items": ["a", "b"] }"#);
+/// assert!(WithContext::<Cart, _>::new(&limits).deserialize(&mut deserializer).is_err());
+/// ```
+pub struct WithContext<'c, T, Ctx: ?Sized> {
+    ctx: &'c Ctx,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'c, T, Ctx: ?Sized> WithContext<'c, T, Ctx> {
+    /// Creates a seed deserializing a `T` with `ctx`.
+    pub fn new(ctx: &'c Ctx) -> Self {
+        WithContext {
+            ctx,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Ctx: ?Sized> Clone for WithContext<'_, T, Ctx> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, Ctx: ?Sized> Copy for WithContext<'_, T, Ctx> {}
+
+impl<'de, T, Ctx> DeserializeSeed<'de> for WithContext<'_, T, Ctx>
+where
+    T: DeserializeWith<'de, Ctx>,
+    Ctx: ?Sized,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_with(deserializer, self.ctx)
+    }
+}
+
+/// Converts a validation error into a deserialization error with [`serde::de::Error::custom`].
+///
+/// This is what `validate_deser` does unless given another `map_err` function.
+pub fn custom<V, E>(err: &V) -> E
+where
+    V: Display + ?Sized,
+    E: serde::de::Error,
+{
+    E::custom(err)
+}
+
+/// Converts a [`ValidationError`] into the matching serde error kind.
+///
+/// A broken `length` rule becomes [`invalid_length`](serde::de::Error::invalid_length) and a broken
+/// `range` rule becomes [`invalid_value`](serde::de::Error::invalid_value), with the error message
+/// as the expectation. Any other error becomes [`custom`](serde::de::Error::custom).
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{validate_deser, Validate};
+///
+/// #[validate_deser(map_err = serde_validate::de::validation_errors)]
+/// #[derive(Debug, Validate)]
+/// struct Post {
+///     #[validate(length(max = 2))]
+///     tags: Vec<String>,
+/// }
+///
+/// let err = serde_json::from_str::<Post>(r#"{ "tags": ["a", "b", "c"] }"#).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid length 3, expected tags: length must be at most 2"
+/// );
+/// ```
+pub fn validation_error<E: serde::de::Error>(error: &ValidationError) -> E {
+    let param = |name: &str| error.params().get(name);
+    match error.code() {
+        "length" => match param("length").and_then(|length| length.parse().ok()) {
+            Some(length) => E::invalid_length(length, &Expecting(error)),
+            None => E::custom(error),
+        },
+        "range" => match param("value") {
+            Some(value) => E::invalid_value(Unexpected::Other(value), &Expecting(error)),
+            None => E::custom(error),
+        },
+        _ => E::custom(error),
+    }
+}
+
+/// Converts [`ValidationErrors`] into the matching serde error kind.
+///
+/// A single error is converted by [`validation_error`]; several errors are reported together with
+/// [`custom`](serde::de::Error::custom).
+pub fn validation_errors<E: serde::de::Error>(errors: &ValidationErrors) -> E {
+    let mut iter = errors.iter();
+    match (iter.next(), iter.next()) {
+        (Some(error), None) => validation_error(error),
+        _ => E::custom(errors),
+    }
+}
+
+/// Describes the expectation of a serde error kind with a validation error.
+struct Expecting<'a>(&'a ValidationError);
+
+impl Expected for Expecting<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Converts a validation error into a deserialization error with `map`, keeping the original error
+/// available to [`from_deserializer`] if it is reported by the target.
+///
+/// This is for the checks made as the value is deserialized, such as the field checks or the
+/// elements of a streamed sequence, which cannot leave their validation to the caller.
+#[doc(hidden)]
+pub fn map_validation_error<V, E>(err: V, map: impl FnOnce(&V) -> E) -> E
+where
+    V: 'static,
+{
+    let de_err = map(&err);
+    if innermost_target().is_some() {
+        CAPTURED.with(|c| *c.borrow_mut() = Some(Box::new(err)));
+    }
+    de_err
+}
@@ -0,0 +1,579 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Structured validation errors.
+//!
+//! `Validate::Error` can be any type, but [`ValidationError`] and [`ValidationErrors`] provide
+//! a common model (code, message, parameters and field path) that the rest of the crate
+//! knows how to build and report.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+/// A single segment of a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// A named field, such as `email`.
+    Field(Cow<'static, str>),
+    /// A position in a sequence or a tuple field, such as `[3]`.
+    Index(usize),
+    /// A key in a map, such as `["en"]`.
+    Key(String),
+}
+
+impl PathSegment {
+    /// Creates a map key segment from any displayable key.
+    pub fn key(key: impl Display) -> Self {
+        PathSegment::Key(key.to_string())
+    }
+}
+
+impl From<&'static str> for PathSegment {
+    fn from(field: &'static str) -> Self {
+        PathSegment::Field(Cow::Borrowed(field))
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(field: String) -> Self {
+        PathSegment::Field(Cow::Owned(field))
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// The location of a value inside the validated instance, such as `items[3].email`.
+///
+/// The empty path refers to the instance itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Path(Vec<PathSegment>);
+
+impl Path {
+    /// Creates the empty path.
+    pub fn new() -> Self {
+        Path(Vec::new())
+    }
+
+    /// Returns `true` if the path refers to the instance itself.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the segments of the path, outermost first.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// Prepends `segment` to the path.
+    pub fn prepend(&mut self, segment: impl Into<PathSegment>) {
+        self.0.insert(0, segment.into());
+    }
+
+    /// Appends `segment` to the path.
+    pub fn push(&mut self, segment: impl Into<PathSegment>) {
+        self.0.push(segment.into());
+    }
+}
+
+impl FromIterator<PathSegment> for Path {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Path(iter.into_iter().collect())
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => write!(f, "{name}")?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Key(key) => write!(f, "[{key:?}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How serious a broken rule is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A finding that does not make the value invalid, such as a deprecated value or a rule that
+    /// is not enforced yet.
+    Warning,
+    /// A broken rule that makes the value invalid.
+    #[default]
+    Error,
+}
+
+/// A single broken rule.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::ValidationError;
+///
+/// let error = ValidationError::new("range")
+///     .with_message("value is out of range")
+///     .with_param("min", 0)
+///     .with_param("max", 100)
+///     .at("age");
+///
+/// assert_eq!(error.code(), "range");
+/// assert_eq!(error.to_string(), "age: value is out of range");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    code: Cow<'static, str>,
+    message: Option<Cow<'static, str>>,
+    params: BTreeMap<Cow<'static, str>, String>,
+    path: Path,
+    severity: Severity,
+}
+
+impl ValidationError {
+    /// Creates an error with the given machine-readable code and an empty path.
+    pub fn new(code: impl Into<Cow<'static, str>>) -> Self {
+        ValidationError {
+            code: code.into(),
+            message: None,
+            params: BTreeMap::new(),
+            path: Path::new(),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Sets the human-readable message.
+    pub fn with_message(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Adds a parameter describing the rule, such as its bounds.
+    pub fn with_param(mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        self.params.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Sets how serious the broken rule is, [`Severity::Error`] by default.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Prepends `segment` to the path of the error.
+    pub fn at(mut self, segment: impl Into<PathSegment>) -> Self {
+        self.path.prepend(segment);
+        self
+    }
+
+    /// Returns the machine-readable code of the broken rule.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the human-readable message, if any.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns the parameters of the broken rule.
+    pub fn params(&self) -> &BTreeMap<Cow<'static, str>, String> {
+        &self.params
+    }
+
+    /// Returns the location of the offending value.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a mutable reference to the location of the offending value.
+    pub fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+
+    /// Returns how serious the broken rule is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let message = self.message.as_deref().unwrap_or(&self.code);
+        if self.path.is_empty() {
+            write!(f, "{message}")
+        } else {
+            write!(f, "{}: {message}", self.path)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A finding that does not make the value invalid, returned by
+/// [`Validate::warnings`](crate::Validate::warnings).
+///
+/// It is a [`ValidationError`] whose severity is always [`Severity::Warning`], and derefs to it.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{Severity, ValidationError, Warning};
+///
+/// let warning = Warning::new("deprecated").with_message("is deprecated").at("format");
+/// assert_eq!(warning.severity(), Severity::Warning);
+/// assert_eq!(warning.to_string(), "format: is deprecated");
+///
+/// let warning = Warning::from(ValidationError::new("unused"));
+/// assert_eq!(warning.severity(), Severity::Warning);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning(ValidationError);
+
+impl Warning {
+    /// Creates a warning with the given machine-readable code and an empty path.
+    pub fn new(code: impl Into<Cow<'static, str>>) -> Self {
+        Warning::from(ValidationError::new(code))
+    }
+
+    /// Sets the human-readable message.
+    pub fn with_message(self, message: impl Into<Cow<'static, str>>) -> Self {
+        Warning(self.0.with_message(message))
+    }
+
+    /// Adds a parameter describing the rule, such as its bounds.
+    pub fn with_param(self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        Warning(self.0.with_param(name, value))
+    }
+
+    /// Prepends `segment` to the path of the warning.
+    pub fn at(self, segment: impl Into<PathSegment>) -> Self {
+        Warning(self.0.at(segment))
+    }
+
+    /// Returns the warning as a [`ValidationError`] of severity [`Severity::Warning`].
+    pub fn into_error(self) -> ValidationError {
+        self.0
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn path_mut(&mut self) -> &mut Path {
+        self.0.path_mut()
+    }
+}
+
+impl From<ValidationError> for Warning {
+    /// Lowers the severity of `error` to [`Severity::Warning`].
+    fn from(error: ValidationError) -> Self {
+        Warning(error.with_severity(Severity::Warning))
+    }
+}
+
+impl Deref for Warning {
+    type Target = ValidationError;
+
+    fn deref(&self) -> &ValidationError {
+        &self.0
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A list of broken rules.
+///
+/// `ValidationErrors` is also a collector: a `validate` implementation can keep checking after
+/// the first failure and report every broken rule at once, with nested errors merged under the
+/// path of the field they come from.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{Validate, ValidationError, ValidationErrors};
+///
+/// struct Item {
+///     email: String,
+/// }
+///
+/// impl Validate for Item {
+///     type Error = ValidationError;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.email.contains('@') {
+///             Ok(())
+///         } else {
+///             Err(ValidationError::new("email").at("email"))
+///         }
+///     }
+/// }
+///
+/// struct Order {
+///     name: String,
+///     items: Vec<Item>,
+/// }
+///
+/// impl Validate for Order {
+///     type Error = ValidationErrors;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         let mut errors = ValidationErrors::new();
+///         if self.name.is_empty() {
+///             errors.push(ValidationError::new("length").at("name"));
+///         }
+///         let mut items = ValidationErrors::new();
+///         for (i, item) in self.items.iter().enumerate() {
+///             items.nest(i, item.validate());
+///         }
+///         errors.extend_at("items", items);
+///         errors.into_result()
+///     }
+/// }
+///
+/// let order = Order {
+///     name: String::new(),
+///     items: vec![Item { email: "a@b.c".into() }, Item { email: "nope".into() }],
+/// };
+/// let errors = order.validate().unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors.to_string(), "name: length; items[1].email: email");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    /// Creates an empty list of errors.
+    pub fn new() -> Self {
+        ValidationErrors(Vec::new())
+    }
+
+    /// Adds an error to the list.
+    pub fn push(&mut self, error: ValidationError) {
+        self.0.push(error);
+    }
+
+    /// Returns `true` if no rule was broken.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of broken rules.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the errors.
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationError> {
+        self.0.iter()
+    }
+
+    /// Records the errors of `result`, if any, without changing their paths.
+    pub fn check<E: Into<ValidationErrors>>(&mut self, result: Result<(), E>) {
+        if let Err(errors) = result {
+            self.0.extend(errors.into().0);
+        }
+    }
+
+    /// Records the errors of `result`, if any, under `segment`.
+    ///
+    /// This is how the errors of a nested value are merged under its field name or index.
+    pub fn nest<E: Into<ValidationErrors>>(
+        &mut self,
+        segment: impl Into<PathSegment>,
+        result: Result<(), E>,
+    ) {
+        if let Err(errors) = result {
+            self.extend_at(segment, errors.into());
+        }
+    }
+
+    /// Adds all the errors of `errors` under `segment`.
+    pub fn extend_at(&mut self, segment: impl Into<PathSegment>, errors: ValidationErrors) {
+        let segment = segment.into();
+        self.0
+            .extend(errors.0.into_iter().map(|error| error.at(segment.clone())));
+    }
+
+    /// Returns the errors whose path displays as `path`, such as `items[3].email`.
+    pub fn at_path<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a ValidationError> {
+        self.0
+            .iter()
+            .filter(move |error| error.path.to_string() == path)
+    }
+
+    /// Groups the errors by path.
+    pub fn by_path(&self) -> BTreeMap<&Path, Vec<&ValidationError>> {
+        let mut map: BTreeMap<&Path, Vec<&ValidationError>> = BTreeMap::new();
+        for error in &self.0 {
+            map.entry(&error.path).or_default().push(error);
+        }
+        map
+    }
+
+    /// Returns `Ok(())` if no rule was broken, or the errors otherwise.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationError> for ValidationErrors {
+    fn from(error: ValidationError) -> Self {
+        ValidationErrors(vec![error])
+    }
+}
+
+impl FromIterator<ValidationError> for ValidationErrors {
+    fn from_iter<I: IntoIterator<Item = ValidationError>>(iter: I) -> Self {
+        ValidationErrors(iter.into_iter().collect())
+    }
+}
+
+impl Extend<ValidationError> for ValidationErrors {
+    fn extend<I: IntoIterator<Item = ValidationError>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for ValidationErrors {
+    type Item = ValidationError;
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a ValidationError;
+    type IntoIter = std::slice::Iter<'a, ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// The findings of validating a value, split by severity.
+///
+/// It is returned by [`Validate::validate_report`](crate::Validate::validate_report).
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{Severity, Validate, ValidationError, ValidationErrors, Warning};
+///
+/// struct Port(u16);
+///
+/// impl Validate for Port {
+///     type Error = ValidationErrors;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.0 == 0 {
+///             return Err(ValidationError::new("zero").into());
+///         }
+///         Ok(())
+///     }
+///
+///     fn warnings(&self) -> Vec<Warning> {
+///         if self.0 < 1024 {
+///             vec![Warning::new("privileged").with_message("is a privileged port")]
+///         } else {
+///             Vec::new()
+///         }
+///     }
+/// }
+///
+/// let report = Port(80).validate_report();
+/// assert!(report.is_valid());
+/// assert_eq!(report.warnings()[0].code(), "privileged");
+/// assert_eq!(report.warnings()[0].severity(), Severity::Warning);
+///
+/// assert!(!Port(0).validate_report().is_valid());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    errors: ValidationErrors,
+    warnings: Vec<Warning>,
+}
+
+impl ValidationReport {
+    /// Creates a report from the broken rules and the warnings of a value.
+    pub fn new(errors: ValidationErrors, warnings: Vec<Warning>) -> Self {
+        ValidationReport { errors, warnings }
+    }
+
+    /// Returns `true` if no rule was broken, warnings aside.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the broken rules.
+    pub fn errors(&self) -> &ValidationErrors {
+        &self.errors
+    }
+
+    /// Returns the warnings.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the warnings if no rule was broken, or the errors otherwise.
+    pub fn into_result(self) -> Result<Vec<Warning>, ValidationErrors> {
+        self.errors.into_result().map(|()| self.warnings)
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.errors)?;
+        for (i, warning) in self.warnings.iter().enumerate() {
+            if i > 0 || !self.errors.is_empty() {
+                write!(f, "; ")?;
+            }
+            write!(f, "warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
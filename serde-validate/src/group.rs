@@ -0,0 +1,80 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Named groups of rules, checked only when validating for a given operation.
+
+use crate::{Validate, ValidateWith};
+use std::fmt::{self, Display, Formatter};
+
+/// A named group of rules, such as the rules checked when an order is created.
+///
+/// Rules declared with `groups = [...]` in `#[derive(Validate)]` are only checked by
+/// [`Validate::validate_group`] with one of their groups, along with the rules of no group.
+///
+/// Every [`Validate`] type implements `ValidateWith<Group>` by validating the group, so a group
+/// can also be picked at runtime with `#[validate_deser(context = Group)]` and the
+/// [`WithContext`](crate::de::WithContext) seed.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{Group, Validate};
+///
+/// #[derive(Validate)]
+/// struct Order {
+///     #[validate(range(min = 1))]
+///     #[validate(range(max = 100), groups = ["create"])]
+///     quantity: u32,
+/// }
+///
+/// const CREATE: Group = Group::new("create");
+///
+/// let order = Order { quantity: 500 };
+/// assert!(order.validate().is_ok());
+/// assert!(order.validate_group(CREATE).is_err());
+/// assert!(order.validate_group(Group::new("import")).is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Group(&'static str);
+
+impl Group {
+    /// Creates the group named `name`.
+    pub const fn new(name: &'static str) -> Self {
+        Group(name)
+    }
+
+    /// Returns the name of the group.
+    pub const fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<T: Validate> ValidateWith<Group> for T {
+    type Error = T::Error;
+
+    fn validate_with(&self, group: &Group) -> Result<(), Self::Error> {
+        self.validate_group(*group)
+    }
+}
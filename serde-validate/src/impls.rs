@@ -0,0 +1,288 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! `Validate` implementations for standard library types.
+//!
+//! Wrappers such as `Option` and `Box` are transparent. Collections validate every element and
+//! report the errors under the element's index or key, so their elements need an error type
+//! convertible into [`ValidationErrors`].
+
+use crate::{Group, PathSegment, Validate, ValidationErrors, Warning};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Display;
+use std::rc::Rc;
+use std::sync::Arc;
+
+impl<T: Validate> Validate for Option<T> {
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        match self {
+            Some(value) => value.validate_group(group),
+            None => Ok(()),
+        }
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        match self {
+            Some(value) => value.warnings(),
+            None => Vec::new(),
+        }
+    }
+}
+
+macro_rules! impl_transparent {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: Validate> Validate for $ty<T> {
+                type Error = T::Error;
+
+                fn validate(&self) -> Result<(), Self::Error> {
+                    (**self).validate()
+                }
+
+                fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+                    (**self).validate_group(group)
+                }
+
+                fn warnings(&self) -> Vec<Warning> {
+                    (**self).warnings()
+                }
+            }
+        )*
+    };
+}
+
+impl_transparent!(Box, Rc, Arc);
+
+/// Validates `value`, for `group` if any.
+fn validate<T: Validate>(value: &T, group: Option<Group>) -> Result<(), T::Error> {
+    match group {
+        Some(group) => value.validate_group(group),
+        None => value.validate(),
+    }
+}
+
+fn validate_indexed<'a, T, I>(iter: I, group: Option<Group>) -> Result<(), ValidationErrors>
+where
+    T: Validate + 'a,
+    T::Error: Into<ValidationErrors>,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut errors = ValidationErrors::new();
+    for (i, value) in iter.into_iter().enumerate() {
+        errors.nest(i, validate(value, group));
+    }
+    errors.into_result()
+}
+
+fn warnings_indexed<'a, T, I>(iter: I) -> Vec<Warning>
+where
+    T: Validate + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut warnings = Vec::new();
+    for (i, value) in iter.into_iter().enumerate() {
+        warnings.extend(value.warnings().into_iter().map(|warning| warning.at(i)));
+    }
+    warnings
+}
+
+fn validate_keyed<'a, K, V, I>(iter: I, group: Option<Group>) -> Result<(), ValidationErrors>
+where
+    K: Display + 'a,
+    V: Validate + 'a,
+    V::Error: Into<ValidationErrors>,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+{
+    let mut errors = ValidationErrors::new();
+    for (key, value) in iter {
+        errors.nest(PathSegment::key(key), validate(value, group));
+    }
+    errors.into_result()
+}
+
+fn warnings_keyed<'a, K, V, I>(iter: I) -> Vec<Warning>
+where
+    K: Display + 'a,
+    V: Validate + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+{
+    let mut warnings = Vec::new();
+    for (key, value) in iter {
+        let segment = PathSegment::key(key);
+        warnings.extend(
+            value
+                .warnings()
+                .into_iter()
+                .map(|warning| warning.at(segment.clone())),
+        );
+    }
+    warnings
+}
+
+impl<T> Validate for Vec<T>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        validate_indexed(self, None)
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        validate_indexed(self, Some(group))
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        warnings_indexed(self)
+    }
+}
+
+impl<T> Validate for VecDeque<T>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        validate_indexed(self, None)
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        validate_indexed(self, Some(group))
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        warnings_indexed(self)
+    }
+}
+
+impl<T, const N: usize> Validate for [T; N]
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        validate_indexed(self, None)
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        validate_indexed(self, Some(group))
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        warnings_indexed(self)
+    }
+}
+
+impl<K, V, S> Validate for HashMap<K, V, S>
+where
+    K: Display,
+    V: Validate,
+    V::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        validate_keyed(self, None)
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        validate_keyed(self, Some(group))
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        warnings_keyed(self)
+    }
+}
+
+impl<K, V> Validate for BTreeMap<K, V>
+where
+    K: Display,
+    V: Validate,
+    V::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        validate_keyed(self, None)
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        validate_keyed(self, Some(group))
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        warnings_keyed(self)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($ty:ident $index:tt),+) => {
+        impl<$($ty),+> Validate for ($($ty,)+)
+        where
+            $($ty: Validate, $ty::Error: Into<ValidationErrors>,)+
+        {
+            type Error = ValidationErrors;
+
+            fn validate(&self) -> Result<(), Self::Error> {
+                let mut errors = ValidationErrors::new();
+                $(errors.nest($index as usize, self.$index.validate());)+
+                errors.into_result()
+            }
+
+            fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+                let mut errors = ValidationErrors::new();
+                $(errors.nest($index as usize, self.$index.validate_group(group));)+
+                errors.into_result()
+            }
+
+            fn warnings(&self) -> Vec<Warning> {
+                let mut warnings = Vec::new();
+                $(
+                    let segment = $index as usize;
+                    warnings.extend(self.$index.warnings().into_iter().map(|w| w.at(segment)));
+                )+
+                warnings
+            }
+        }
+    };
+}
+
+impl_tuple!(A 0);
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
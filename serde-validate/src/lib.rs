@@ -61,6 +61,64 @@
 //! let my_struct: Result<MyStruct, _> = serde_json::from_str(bad_json_input);
 //! assert!(my_struct.is_err());
 //! ```
+//!
+//! ## Deriving `Validate`
+//!
+//! Simple rules can be declared on the fields instead of implementing `Validate` by hand.
+//!
+//! ```rust
+//! use serde_validate::{validate_deser, Validate};
+//!
+//! #[validate_deser]
+//! #[derive(Validate)]
+//! struct User {
+//!     #[validate(length(min = 1))]
+//!     name: String,
+//!     #[validate(range(min = 0, max = 150))]
+//!     age: i32,
+//! }
+//!
+//! let user: Result<User, _> = serde_json::from_str(r#"{ "name": "", "age": 200 }"#);
+//! assert!(user.is_err());
+//! ```
+
+mod async_validate;
+mod bounded;
+mod error;
+mod group;
+mod impls;
+mod repair;
+pub mod rules;
+mod sanitize;
+pub mod sanitizers;
+mod validated;
+
+pub use async_validate::{AsyncValidate, Pending};
+pub use bounded::{BoundedString, BoundedVec};
+pub use error::{
+    Path, PathSegment, Severity, ValidationError, ValidationErrors, ValidationReport, Warning,
+};
+pub use group::Group;
+pub use repair::RepairAction;
+pub use validated::Validated;
+
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "serde")]
+mod limit;
+#[cfg(feature = "serde")]
+mod stream;
+
+#[cfg(feature = "serde")]
+pub use de::{
+    from_deserializer, from_deserializer_with, from_deserializer_with_warnings, DeserializeError,
+};
+#[cfg(feature = "serde")]
+pub use stream::{ValidatedMap, ValidatedSeq};
+
+/// The `serde` crate used by the generated code, so users need no direct dependency on it.
+#[cfg(feature = "serde")]
+pub use serde;
 
 /// The `Validate` trait defines the contract for validating deserialized structs.
 ///
@@ -98,13 +156,129 @@ pub trait Validate: Sized {
     /// Validates the instance, returning `Ok(())` if serde-validate, or an `Error` otherwise.
     fn validate(&self) -> Result<(), Self::Error>;
 
+    /// Validates the instance for `group`, checking the rules of the group along with the rules
+    /// of no group.
+    ///
+    /// Types without groups are validated as by [`Validate::validate`], which is the default.
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        let _ = group;
+        self.validate()
+    }
+
+    /// Returns the findings that do not make the instance invalid, such as deprecated values.
+    ///
+    /// They never fail validation nor deserialization. Returns no warning by default.
+    fn warnings(&self) -> Vec<Warning> {
+        Vec::new()
+    }
+
+    /// Validates the instance, returning its broken rules and its warnings together.
+    fn validate_report(&self) -> ValidationReport
+    where
+        Self::Error: Into<ValidationErrors>,
+    {
+        let errors = self.validate().err().map(Into::into).unwrap_or_default();
+        ValidationReport::new(errors, self.warnings())
+    }
+
     /// Consumes the instance, validating it and returning the instance itself if serde-validate.
     ///
     /// This method provides a convenient way to validate and immediately use the instance.
     fn validated(self) -> Result<Self, Self::Error> {
         self.validate().map(|_| self)
     }
+
+    /// Consumes the instance, validating it and wrapping it in a [`Validated`] if valid.
+    fn into_validated(self) -> Result<Validated<Self>, Self::Error> {
+        Validated::new(self)
+    }
+}
+
+/// The `ValidateWith` trait defines validation that depends on runtime state, such as the set
+/// of known tenants or the current configuration limits.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::ValidateWith;
+/// use std::collections::HashSet;
+///
+/// struct Request {
+///     tenant: String,
+/// }
+///
+/// impl ValidateWith<HashSet<String>> for Request {
+///     type Error = String;
+///
+///     fn validate_with(&self, tenants: &HashSet<String>) -> Result<(), Self::Error> {
+///         if tenants.contains(&self.tenant) {
+///             Ok(())
+///         } else {
+///             Err(format!("unknown tenant {}", self.tenant))
+///         }
+///     }
+/// }
+///
+/// let tenants = HashSet::from(["acme".to_string()]);
+/// let request = Request { tenant: "acme".into() };
+/// assert!(request.validate_with(&tenants).is_ok());
+/// ```
+pub trait ValidateWith<Ctx: ?Sized>: Sized {
+    /// The error type returned by the `validate_with` method.
+    type Error;
+
+    /// Validates the instance against `ctx`, returning `Ok(())` if valid, or an `Error` otherwise.
+    fn validate_with(&self, ctx: &Ctx) -> Result<(), Self::Error>;
+
+    /// Consumes the instance, validating it against `ctx` and returning the instance itself if
+    /// valid.
+    fn validated_with(self, ctx: &Ctx) -> Result<Self, Self::Error> {
+        self.validate_with(ctx).map(|_| self)
+    }
+}
+
+/// The `Sanitize` trait normalizes a value in place, such as trimming its strings, before it is
+/// validated.
+///
+/// `validate_deser` applies the `#[sanitizer(...)]` attributes of the fields while deserializing
+/// them, so the rules only ever see sanitized values.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{sanitizers, Sanitize};
+///
+/// struct Email(String);
+///
+/// impl Sanitize for Email {
+///     fn sanitize(&mut self) {
+///         sanitizers::trim(&mut self.0);
+///         sanitizers::lowercase(&mut self.0);
+///     }
+/// }
+///
+/// let mut email = Email(" Ann@Example.com ".into());
+/// email.sanitize();
+/// assert_eq!(email.0, "ann@example.com");
+/// ```
+pub trait Sanitize {
+    /// Normalizes the instance in place.
+    fn sanitize(&mut self);
 }
 
 #[cfg(feature = "macro")]
-pub use serde_validate_macro::validate_deser;
+pub use serde_validate_macro::{
+    validate_deser, validate_ser, validate_serde, AsyncValidate, Sanitize, Validate,
+};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::async_validate::{join_all, NestedCheck};
+    #[cfg(feature = "serde")]
+    pub use crate::de::{
+        defer_validation, map_validation_error, nest_repairs, record_repair, repairs_len,
+        skips_field_checks, skips_repairs, skips_validation, Frame,
+    };
+    #[cfg(feature = "serde")]
+    pub use crate::repair::SkipInvalid;
+}
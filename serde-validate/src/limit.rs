@@ -0,0 +1,485 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A `Deserializer` wrapper refusing oversize input while it is visited.
+//!
+//! The wrapper forwards every call to the wrapped deserializer, wrapping in turn the visitor,
+//! the sequence, map and enum accesses, and the seeds of their elements, so the limits follow
+//! the input all the way down.
+
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, IgnoredAny, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::fmt::{self, Formatter};
+
+/// Returns the error of a sequence or map with more than `max` elements.
+pub(crate) fn too_many<E: de::Error>(max: usize) -> E {
+    E::custom(format_args!("too many items, expected at most {max}"))
+}
+
+/// Fails if a sequence or map announces more than `max` elements.
+pub(crate) fn check_size_hint<E: de::Error>(size_hint: Option<usize>, max: usize) -> Result<(), E> {
+    match size_hint {
+        Some(len) if len > max => Err(too_many(max)),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if `seq`, which already yielded `max` elements, has another one.
+///
+/// The extra element is skipped rather than deserialized, so nothing is allocated for it.
+pub(crate) fn check_seq_end<'de, A: SeqAccess<'de>>(
+    seq: &mut A,
+    max: usize,
+) -> Result<(), A::Error> {
+    match seq.next_element::<IgnoredAny>()? {
+        Some(_) => Err(too_many(max)),
+        None => Ok(()),
+    }
+}
+
+/// The limits applying to a value of the input.
+#[derive(Clone, Copy, Default)]
+struct Limits {
+    /// The number of sequences, maps and enums enclosing the value.
+    depth: usize,
+    max_depth: Option<usize>,
+    /// The maximum number of elements of the value, if it is a sequence or a map.
+    max_items: Option<usize>,
+}
+
+impl Limits {
+    /// Enters a sequence, map or enum, returning the limits of its elements.
+    fn enter<E: de::Error>(self) -> Result<Limits, E> {
+        match self.max_depth {
+            Some(max_depth) if self.depth >= max_depth => Err(E::custom(format_args!(
+                "nesting too deep, expected at most {max_depth} levels"
+            ))),
+            _ => Ok(Limits {
+                depth: self.depth + 1,
+                max_depth: self.max_depth,
+                max_items: None,
+            }),
+        }
+    }
+}
+
+/// A [`Deserializer`] refusing input beyond configurable limits while visiting it.
+///
+/// Formats usually check their input only as far as the data model goes, so a deeply nested or
+/// very long document is read, and allocated, before any validation gets to reject it. Wrapping
+/// the deserializer in a `Limited` rejects such input as soon as a limit is crossed.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_validate::de::Limited;
+///
+/// let mut deserializer = serde_json::Deserializer::from_str("[[[1]]]");
+/// let value = Vec::<Vec<Vec<u8>>>::deserialize(Limited::new(&mut deserializer).with_max_depth(3));
+/// assert!(value.is_ok());
+///
+/// let mut deserializer = serde_json::Deserializer::from_str("[[[1]]]");
+/// let err = Vec::<Vec<Vec<u8>>>::deserialize(Limited::new(&mut deserializer).with_max_depth(2))
+///     .unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "nesting too deep, expected at most 2 levels at line 1 column 3"
+/// );
+/// ```
+pub struct Limited<D> {
+    inner: D,
+    limits: Limits,
+}
+
+impl<D> Limited<D> {
+    /// Wraps `deserializer`, with no limits yet.
+    pub fn new(deserializer: D) -> Self {
+        Limited {
+            inner: deserializer,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Limits the nesting of sequences, maps and enums to `max_depth` levels.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.limits.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Limits the number of elements of the outermost sequence or map to `max_items`.
+    ///
+    /// The elements themselves are not limited. Once the limit is reached, any further element
+    /// is skipped without being deserialized before failing.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.limits.max_items = Some(max_items);
+        self
+    }
+
+    fn wrap<V>(&self, visitor: V) -> LimitedVisitor<V> {
+        LimitedVisitor {
+            inner: visitor,
+            limits: self.limits,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = self.wrap(visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Limited<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// A visitor wrapping the accesses it is given with the limits of its value.
+struct LimitedVisitor<V> {
+    inner: V,
+    limits: Limits,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<E>(self, $($arg: $ty),*) -> Result<V::Value, E>
+            where
+                E: de::Error,
+            {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for LimitedVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(v: bool);
+        visit_i8(v: i8);
+        visit_i16(v: i16);
+        visit_i32(v: i32);
+        visit_i64(v: i64);
+        visit_i128(v: i128);
+        visit_u8(v: u8);
+        visit_u16(v: u16);
+        visit_u32(v: u32);
+        visit_u64(v: u64);
+        visit_u128(v: u128);
+        visit_f32(v: f32);
+        visit_f64(v: f64);
+        visit_char(v: char);
+        visit_str(v: &str);
+        visit_borrowed_str(v: &'de str);
+        visit_string(v: String);
+        visit_bytes(v: &[u8]);
+        visit_borrowed_bytes(v: &'de [u8]);
+        visit_byte_buf(v: Vec<u8>);
+        visit_none();
+        visit_unit();
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_some(Limited {
+            inner: deserializer,
+            limits: self.limits,
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_newtype_struct(Limited {
+            inner: deserializer,
+            limits: self.limits,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let elements = self.limits.enter()?;
+        if let Some(max_items) = self.limits.max_items {
+            check_size_hint(seq.size_hint(), max_items)?;
+        }
+        self.inner.visit_seq(LimitedSeq {
+            inner: seq,
+            elements,
+            max_items: self.limits.max_items,
+            count: 0,
+        })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let entries = self.limits.enter()?;
+        if let Some(max_items) = self.limits.max_items {
+            check_size_hint(map.size_hint(), max_items)?;
+        }
+        self.inner.visit_map(LimitedMap {
+            inner: map,
+            entries,
+            max_items: self.limits.max_items,
+            count: 0,
+        })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let contents = self.limits.enter()?;
+        self.inner.visit_enum(LimitedEnum {
+            inner: data,
+            contents,
+        })
+    }
+}
+
+/// A seed deserializing its value through a [`Limited`] deserializer.
+struct LimitedSeed<T> {
+    inner: T,
+    limits: Limits,
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for LimitedSeed<T> {
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.deserialize(Limited {
+            inner: deserializer,
+            limits: self.limits,
+        })
+    }
+}
+
+struct LimitedSeq<A> {
+    inner: A,
+    /// The limits of the elements.
+    elements: Limits,
+    max_items: Option<usize>,
+    count: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for LimitedSeq<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(max_items) = self.max_items {
+            if self.count == max_items {
+                check_seq_end(&mut self.inner, max_items)?;
+                return Ok(None);
+            }
+        }
+        self.count += 1;
+        self.inner.next_element_seed(LimitedSeed {
+            inner: seed,
+            limits: self.elements,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct LimitedMap<A> {
+    inner: A,
+    /// The limits of the keys and values.
+    entries: Limits,
+    max_items: Option<usize>,
+    count: usize,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for LimitedMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if let Some(max_items) = self.max_items {
+            if self.count == max_items {
+                return match self.inner.next_key::<IgnoredAny>()? {
+                    Some(_) => Err(too_many(max_items)),
+                    None => Ok(None),
+                };
+            }
+        }
+        self.count += 1;
+        self.inner.next_key_seed(LimitedSeed {
+            inner: seed,
+            limits: self.entries,
+        })
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(LimitedSeed {
+            inner: seed,
+            limits: self.entries,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct LimitedEnum<A> {
+    inner: A,
+    /// The limits of the variant's contents.
+    contents: Limits,
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for LimitedEnum<A> {
+    type Error = A::Error;
+    type Variant = LimitedEnum<A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (variant, contents) = self.inner.variant_seed(seed)?;
+        Ok((
+            variant,
+            LimitedEnum {
+                inner: contents,
+                contents: self.contents,
+            },
+        ))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for LimitedEnum<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.newtype_variant_seed(LimitedSeed {
+            inner: seed,
+            limits: self.contents,
+        })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.tuple_variant(
+            len,
+            LimitedVisitor {
+                inner: visitor,
+                limits: self.contents,
+            },
+        )
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.struct_variant(
+            fields,
+            LimitedVisitor {
+                inner: visitor,
+                limits: self.contents,
+            },
+        )
+    }
+}
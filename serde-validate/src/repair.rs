@@ -0,0 +1,240 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The `on_invalid` policies repairing invalid values while deserializing.
+
+#[cfg(feature = "serde")]
+use crate::de::next_valid;
+#[cfg(feature = "serde")]
+use crate::{PathSegment, Validate, ValidationErrors};
+#[cfg(feature = "serde")]
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "serde")]
+use std::hash::{BuildHasher, Hash};
+#[cfg(feature = "serde")]
+use std::marker::PhantomData;
+
+/// How an invalid value was repaired.
+///
+/// A repair is reported by [`from_deserializer_with_warnings`] as a [`Warning`] with the code
+/// `repaired`, located at the repaired value. Its `action` parameter is the
+/// [`policy`](RepairAction::policy) and its message tells how the value was repaired and the rules
+/// it broke.
+///
+/// [`from_deserializer_with_warnings`]: crate::from_deserializer_with_warnings
+/// [`Warning`]: crate::Warning
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::{from_deserializer_with_warnings, validate_deser, RepairAction, Validate};
+///
+/// #[validate_deser]
+/// #[derive(Validate)]
+/// struct Config {
+///     #[validate(range(min = 1, max = 10), on_invalid = "clamp")]
+///     retries: u32,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{ "retries": 50 }"#);
+/// let (config, warnings) =
+///     from_deserializer_with_warnings::<Config, _>(&mut deserializer).unwrap();
+/// assert_eq!(config.retries, 10);
+/// assert_eq!(warnings[0].code(), "repaired");
+/// assert_eq!(warnings[0].params()["action"], RepairAction::Clamp.policy());
+/// assert_eq!(warnings[0].to_string(), "retries: clamped (must be between 1 and 10)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepairAction {
+    /// The value was replaced by its default, from `on_invalid = "default"`.
+    Default,
+    /// The element was dropped from its collection, from `on_invalid = "skip"`.
+    Skip,
+    /// The value was clamped into its range, from `on_invalid = "clamp"`.
+    Clamp,
+}
+
+impl RepairAction {
+    /// Returns the `on_invalid` policy that made the repair, such as `"clamp"`.
+    pub fn policy(&self) -> &'static str {
+        match self {
+            RepairAction::Default => "default",
+            RepairAction::Skip => "skip",
+            RepairAction::Clamp => "clamp",
+        }
+    }
+}
+
+impl Display for RepairAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RepairAction::Default => write!(f, "replaced by its default"),
+            RepairAction::Skip => write!(f, "skipped"),
+            RepairAction::Clamp => write!(f, "clamped"),
+        }
+    }
+}
+
+/// A collection whose invalid elements can be dropped while deserializing it, for
+/// `on_invalid = "skip"`.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub trait SkipInvalid<'de>: Sized {
+    /// Deserializes the collection, dropping the invalid elements and recording a repair located
+    /// at each of them.
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+#[cfg(feature = "serde")]
+impl<'de, C: SkipInvalid<'de>> SkipInvalid<'de> for Option<C> {
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_option(OptionVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> SkipInvalid<'de> for Vec<T>
+where
+    T: Deserialize<'de> + Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> SkipInvalid<'de> for VecDeque<T>
+where
+    T: Deserialize<'de> + Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize_skipping(deserializer).map(VecDeque::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V, S> SkipInvalid<'de> for HashMap<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash + Display,
+    V: Deserialize<'de> + Validate,
+    V::Error: Into<ValidationErrors>,
+    S: BuildHasher + Default,
+{
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> SkipInvalid<'de> for BTreeMap<K, V>
+where
+    K: Deserialize<'de> + Ord + Display,
+    V: Deserialize<'de> + Validate,
+    V::Error: Into<ValidationErrors>,
+{
+    fn deserialize_skipping<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct OptionVisitor<C>(PhantomData<C>);
+
+#[cfg(feature = "serde")]
+impl<'de, C: SkipInvalid<'de>> Visitor<'de> for OptionVisitor<C> {
+    type Value = Option<C>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("an option")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        C::deserialize_skipping(deserializer).map(Some)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SeqVisitor<T>(PhantomData<T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T> Visitor<'de> for SeqVisitor<T>
+where
+    T: Deserialize<'de> + Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        let mut index = 0;
+        while let Some(value) = next_valid(index, || seq.next_element())? {
+            values.extend(value);
+            index += 1;
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct MapVisitor<K, V, M>(PhantomData<(K, V, M)>);
+
+#[cfg(feature = "serde")]
+impl<'de, K, V, M> Visitor<'de> for MapVisitor<K, V, M>
+where
+    K: Deserialize<'de> + Display,
+    V: Deserialize<'de> + Validate,
+    V::Error: Into<ValidationErrors>,
+    M: Default + Extend<(K, V)>,
+{
+    type Value = M;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = M::default();
+        while let Some(key) = map.next_key::<K>()? {
+            let segment = PathSegment::key(&key);
+            if let Some(value) = next_valid(segment, || map.next_value().map(Some))?.flatten() {
+                entries.extend([(key, value)]);
+            }
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,241 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Built-in rules used by `#[derive(Validate)]`.
+//!
+//! Each rule returns a [`ValidationError`] with an empty path; the derive places it under the
+//! field it was declared on. The rules can also be called from hand-written `validate` impls.
+//! [`exactly_one_of`], which spans several fields, locates its errors itself.
+
+use crate::{Path, PathSegment, ValidationError, ValidationErrors};
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+
+/// Types with a length that can be checked by [`length`].
+///
+/// Strings are measured in characters, collections in elements.
+pub trait HasLength {
+    /// Returns the length of the value.
+    fn length(&self) -> usize;
+}
+
+impl HasLength for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl HasLength for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> HasLength for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, const N: usize> HasLength for [T; N] {
+    fn length(&self) -> usize {
+        N
+    }
+}
+
+impl<T> HasLength for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> HasLength for VecDeque<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V, S> HasLength for HashMap<K, V, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> HasLength for BTreeMap<K, V> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, S> HasLength for HashSet<T, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> HasLength for BTreeSet<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: HasLength + ?Sized> HasLength for &T {
+    fn length(&self) -> usize {
+        (**self).length()
+    }
+}
+
+impl<T: HasLength + ?Sized> HasLength for Box<T> {
+    fn length(&self) -> usize {
+        (**self).length()
+    }
+}
+
+/// Checks that `value` lies within `min` and `max`, both inclusive.
+///
+/// A value that cannot be compared with a bound, such as a NaN, does not lie within it.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::rules::range;
+///
+/// assert!(range(&5, Some(&0), Some(&10)).is_ok());
+/// assert_eq!(range(&11, Some(&0), Some(&10)).unwrap_err().code(), "range");
+/// assert!(range(&f64::NAN, Some(&0.0), None).is_err());
+/// ```
+pub fn range<T>(value: &T, min: Option<&T>, max: Option<&T>) -> Result<(), ValidationError>
+where
+    T: PartialOrd + Display + ?Sized,
+{
+    let below = min.is_some_and(|min| !matches!(value.partial_cmp(min), Some(Equal | Greater)));
+    let above = max.is_some_and(|max| !matches!(value.partial_cmp(max), Some(Less | Equal)));
+    if !below && !above {
+        return Ok(());
+    }
+    let message = match (min, max) {
+        (Some(min), Some(max)) => format!("must be between {min} and {max}"),
+        (Some(min), None) => format!("must be at least {min}"),
+        (None, Some(max)) => format!("must be at most {max}"),
+        (None, None) => unreachable!(),
+    };
+    let mut error = ValidationError::new("range")
+        .with_message(message)
+        .with_param("value", value);
+    if let Some(min) = min {
+        error = error.with_param("min", min);
+    }
+    if let Some(max) = max {
+        error = error.with_param("max", max);
+    }
+    Err(error)
+}
+
+/// Checks that the length of `value` lies within `min` and `max`, both inclusive.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::rules::length;
+///
+/// assert!(length("abc", Some(1), None).is_ok());
+/// assert_eq!(length("", Some(1), None).unwrap_err().code(), "length");
+/// ```
+pub fn length<T>(value: &T, min: Option<usize>, max: Option<usize>) -> Result<(), ValidationError>
+where
+    T: HasLength + ?Sized,
+{
+    let length = value.length();
+    let below = min.is_some_and(|min| length < min);
+    let above = max.is_some_and(|max| length > max);
+    if !below && !above {
+        return Ok(());
+    }
+    let message = match (min, max) {
+        (Some(min), Some(max)) if min == max => format!("length must be exactly {min}"),
+        (Some(min), Some(max)) => format!("length must be between {min} and {max}"),
+        (Some(min), None) => format!("length must be at least {min}"),
+        (None, Some(max)) => format!("length must be at most {max}"),
+        (None, None) => unreachable!(),
+    };
+    let mut error = ValidationError::new("length")
+        .with_message(message)
+        .with_param("length", length);
+    if let Some(min) = min {
+        error = error.with_param("min", min);
+    }
+    if let Some(max) = max {
+        error = error.with_param("max", max);
+    }
+    Err(error)
+}
+
+/// Checks that the optional `value` is set.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::rules::required;
+///
+/// assert!(required(&Some(1)).is_ok());
+/// assert_eq!(required(&None::<i32>).unwrap_err().code(), "required");
+/// ```
+pub fn required<T>(value: &Option<T>) -> Result<(), ValidationError> {
+    match value {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("required").with_message("is required")),
+    }
+}
+
+/// Checks that exactly one of `fields`, given with whether each of them is set, is set.
+///
+/// The errors are located at every field when none is set, and at every set field when more
+/// than one is.
+///
+/// # Example
+///
+/// ```
+/// use serde_validate::rules::exactly_one_of;
+///
+/// assert!(exactly_one_of(&[("email".into(), true), ("phone".into(), false)]).is_ok());
+/// let errors = exactly_one_of(&[("email".into(), true), ("phone".into(), true)]).unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors.to_string(), "email: exactly one of email, phone must be set; \
+///     phone: exactly one of email, phone must be set");
+/// ```
+pub fn exactly_one_of(fields: &[(PathSegment, bool)]) -> Result<(), ValidationErrors> {
+    let set = fields.iter().filter(|(_, set)| *set).count();
+    if set == 1 {
+        return Ok(());
+    }
+    let names = fields
+        .iter()
+        .map(|(segment, _)| Path::from_iter([segment.clone()]).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let error = ValidationError::new("exactly_one_of")
+        .with_message(format!("exactly one of {names} must be set"))
+        .with_param("fields", &names);
+    Err(fields
+        .iter()
+        .filter(|(_, is_set)| set == 0 || *is_set)
+        .map(|(segment, _)| error.clone().at(segment.clone()))
+        .collect())
+}
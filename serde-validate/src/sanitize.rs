@@ -0,0 +1,75 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! `Sanitize` implementations for standard library types.
+//!
+//! Wrappers and collections sanitize every value they hold.
+
+use crate::Sanitize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+impl<T: Sanitize> Sanitize for Option<T> {
+    fn sanitize(&mut self) {
+        if let Some(value) = self {
+            value.sanitize();
+        }
+    }
+}
+
+impl<T: Sanitize + ?Sized> Sanitize for Box<T> {
+    fn sanitize(&mut self) {
+        (**self).sanitize();
+    }
+}
+
+impl<T: Sanitize> Sanitize for [T] {
+    fn sanitize(&mut self) {
+        self.iter_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<T: Sanitize, const N: usize> Sanitize for [T; N] {
+    fn sanitize(&mut self) {
+        self.iter_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<T: Sanitize> Sanitize for Vec<T> {
+    fn sanitize(&mut self) {
+        self.iter_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<T: Sanitize> Sanitize for VecDeque<T> {
+    fn sanitize(&mut self) {
+        self.iter_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<K, V: Sanitize, S> Sanitize for HashMap<K, V, S> {
+    fn sanitize(&mut self) {
+        self.values_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<K, V: Sanitize> Sanitize for BTreeMap<K, V> {
+    fn sanitize(&mut self) {
+        self.values_mut().for_each(Sanitize::sanitize);
+    }
+}
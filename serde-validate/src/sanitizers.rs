@@ -0,0 +1,96 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Built-in sanitizers used by `#[derive(Sanitize)]`.
+//!
+//! Each sanitizer normalizes a string in place. They can also be called from hand-written
+//! `sanitize` impls.
+
+/// Removes the leading and trailing whitespace.
+///
+/// # Example
+///
+/// ```
+/// let mut value = String::from("  Ann \n");
+/// serde_validate::sanitizers::trim(&mut value);
+/// assert_eq!(value, "Ann");
+/// ```
+pub fn trim(value: &mut String) {
+    value.truncate(value.trim_end().len());
+    let start = value.len() - value.trim_start().len();
+    value.drain(..start);
+}
+
+/// Converts the string to lowercase.
+pub fn lowercase(value: &mut String) {
+    *value = value.to_lowercase();
+}
+
+/// Converts the string to uppercase.
+pub fn uppercase(value: &mut String) {
+    *value = value.to_uppercase();
+}
+
+/// Replaces every run of whitespace with a single space.
+///
+/// Leading and trailing runs are collapsed too, not removed; combine with [`trim`] for that.
+///
+/// # Example
+///
+/// ```
+/// let mut value = String::from("New \t  York");
+/// serde_validate::sanitizers::collapse_whitespace(&mut value);
+/// assert_eq!(value, "New York");
+/// ```
+pub fn collapse_whitespace(value: &mut String) {
+    let mut collapsed = String::with_capacity(value.len());
+    let mut in_whitespace = false;
+    for c in value.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    *value = collapsed;
+}
+
+/// Normalizes the string to Unicode Normalization Form C.
+///
+/// Requires the `unicode` feature.
+///
+/// # Example
+///
+/// ```
+/// let mut value = String::from("Cafe\u{301}");
+/// serde_validate::sanitizers::nfc(&mut value);
+/// assert_eq!(value, "Caf\u{e9}");
+/// ```
+#[cfg(feature = "unicode")]
+pub fn nfc(value: &mut String) {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+    if is_nfc_quick(value.chars()) != IsNormalized::Yes {
+        *value = value.nfc().collect();
+    }
+}
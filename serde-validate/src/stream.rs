@@ -0,0 +1,348 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Collections validating their elements while they are deserialized.
+
+use crate::de::{next_checked, Frame};
+use crate::{Group, PathSegment, Validate, ValidationErrors, Warning};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// The most elements preallocated from a size hint, so a hostile hint cannot exhaust memory.
+const MAX_PREALLOCATED: usize = 1024;
+
+/// A sequence whose elements are validated as soon as each one is deserialized.
+///
+/// Deserializing a `Vec<T>` and then validating it buffers the whole sequence before the first
+/// invalid element is found. A `ValidatedSeq<T>` stops at the first invalid element instead,
+/// with its index in the error, without reading the rest of the input.
+///
+/// It serializes and deserializes as a sequence. Each element is checked once with `T::validate`,
+/// whose errors are reported under the element's index. A `validate_deser` element leaves its own
+/// validation to the sequence, and the errors of its field checks are reported under its index too.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_validate::{ValidatedSeq, Validate};
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Item {
+///     #[validate(range(min = 1))]
+///     quantity: u32,
+/// }
+///
+/// let items: ValidatedSeq<Item> =
+///     serde_json::from_str(r#"[{ "quantity": 1 }, { "quantity": 2 }]"#).unwrap();
+/// assert_eq!(items.len(), 2);
+///
+/// let err = serde_json::from_str::<ValidatedSeq<Item>>(
+///     r#"[{ "quantity": 1 }, { "quantity": 0 }, { "oops": true }]"#,
+/// )
+/// .err()
+/// .unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "[1].quantity: must be at least 1 at line 1 column 39"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidatedSeq<T>(Vec<T>);
+
+impl<T> ValidatedSeq<T>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    /// Validates every element of `values`, wrapping them if all are valid.
+    pub fn new(values: Vec<T>) -> Result<Self, ValidationErrors> {
+        values.validated().map(ValidatedSeq)
+    }
+}
+
+impl<T> ValidatedSeq<T> {
+    /// Returns the validated elements.
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedSeq<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> IntoIterator for ValidatedSeq<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ValidatedSeq<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T> Validate for ValidatedSeq<T>
+where
+    T: Validate,
+    T::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        self.0.validate()
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        self.0.validate_group(group)
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        self.0.warnings()
+    }
+}
+
+impl<T: Serialize> Serialize for ValidatedSeq<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ValidatedSeq<T>
+where
+    T: Validate + Deserialize<'de>,
+    T::Error: Into<ValidationErrors>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let _frame = Frame::validating::<Self>();
+
+        struct SeqVisitor<T>(PhantomData<fn() -> T>);
+
+        impl<'de, T> Visitor<'de> for SeqVisitor<T>
+        where
+            T: Validate + Deserialize<'de>,
+            T::Error: Into<ValidationErrors>,
+        {
+            type Value = ValidatedSeq<T>;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let capacity = seq.size_hint().unwrap_or(0).min(MAX_PREALLOCATED);
+                let mut values = Vec::with_capacity(capacity);
+                while let Some(value) = next_checked(values.len(), || seq.next_element::<T>())? {
+                    values.push(value);
+                }
+                Ok(ValidatedSeq(values))
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
+/// A map whose values are validated as soon as each entry is deserialized.
+///
+/// This is the map counterpart of [`ValidatedSeq`]: it stops at the first invalid value, with its
+/// key in the error. Duplicate keys keep the last value, as with `HashMap`.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_validate::{ValidatedMap, Validate, ValidationError};
+///
+/// #[derive(Deserialize)]
+/// struct Price(i64);
+///
+/// impl Validate for Price {
+///     type Error = ValidationError;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.0 >= 0 {
+///             Ok(())
+///         } else {
+///             Err(ValidationError::new("negative"))
+///         }
+///     }
+/// }
+///
+/// let err = serde_json::from_str::<ValidatedMap<String, Price>>(r#"{ "tea": 3, "cake": -1 }"#)
+///     .err()
+///     .unwrap();
+/// assert_eq!(err.to_string(), "[\"cake\"]: negative at line 1 column 24");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedMap<K, V>(HashMap<K, V>)
+where
+    K: Eq + Hash;
+
+impl<K, V> ValidatedMap<K, V>
+where
+    K: Eq + Hash + Display,
+    V: Validate,
+    V::Error: Into<ValidationErrors>,
+{
+    /// Validates every value of `map`, wrapping it if all are valid.
+    pub fn new(map: HashMap<K, V>) -> Result<Self, ValidationErrors> {
+        map.validated().map(ValidatedMap)
+    }
+}
+
+impl<K: Eq + Hash, V> ValidatedMap<K, V> {
+    /// Returns the validated map.
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.0
+    }
+}
+
+impl<K: Eq + Hash, V> Deref for ValidatedMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &HashMap<K, V> {
+        &self.0
+    }
+}
+
+impl<K: Eq + Hash, V> IntoIterator for ValidatedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::collections::hash_map::IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K: Eq + Hash, V> IntoIterator for &'a ValidatedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = std::collections::hash_map::Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<K, V> Validate for ValidatedMap<K, V>
+where
+    K: Eq + Hash + Display,
+    V: Validate,
+    V::Error: Into<ValidationErrors>,
+{
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        self.0.validate()
+    }
+
+    fn validate_group(&self, group: Group) -> Result<(), Self::Error> {
+        self.0.validate_group(group)
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        self.0.warnings()
+    }
+}
+
+impl<K, V> Serialize for ValidatedMap<K, V>
+where
+    K: Eq + Hash + Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for ValidatedMap<K, V>
+where
+    K: Eq + Hash + Display + Deserialize<'de>,
+    V: Validate + Deserialize<'de>,
+    V::Error: Into<ValidationErrors>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let _frame = Frame::validating::<Self>();
+
+        struct MapVisitor<K, V>(PhantomData<fn() -> (K, V)>);
+
+        impl<'de, K, V> Visitor<'de> for MapVisitor<K, V>
+        where
+            K: Eq + Hash + Display + Deserialize<'de>,
+            V: Validate + Deserialize<'de>,
+            V::Error: Into<ValidationErrors>,
+        {
+            type Value = ValidatedMap<K, V>;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let capacity = map.size_hint().unwrap_or(0).min(MAX_PREALLOCATED);
+                let mut values = HashMap::with_capacity(capacity);
+                while let Some(key) = map.next_key::<K>()? {
+                    let segment = PathSegment::key(&key);
+                    if let Some(value) = next_checked(segment, || map.next_value::<V>().map(Some))?
+                    {
+                        values.insert(key, value);
+                    }
+                }
+                Ok(ValidatedMap(values))
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
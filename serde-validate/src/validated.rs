@@ -0,0 +1,247 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A wrapper proving that a value passed validation.
+
+use crate::Validate;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+/// A value that passed validation.
+///
+/// A `Validated<T>` can only be obtained by validating a `T`, either with [`Validated::new`],
+/// [`Validate::into_validated`] or by deserializing it. It gives shared access to the value but
+/// no mutable access, so the invariant checked by `T::validate` holds for as long as it lives.
+/// Changes go through [`Validated::modify`], [`Validated::into_modified`] or
+/// [`Validated::try_map`], which validate again.
+///
+/// `Validated<T>` serializes and deserializes as `T`, validating on deserialization. This makes
+/// it usable with plain `#[derive(Deserialize)]` types, without `validate_deser`. A
+/// `validate_deser` type is validated once, by the wrapper. Even
+/// [`de::deserialize_unvalidated`](crate::de::deserialize_unvalidated) validates it, since a
+/// `Validated<T>` cannot hold an unvalidated value.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_validate::{Validate, Validated};
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     quantity: u32,
+/// }
+///
+/// impl Validate for Order {
+///     type Error = String;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.quantity > 0 {
+///             Ok(())
+///         } else {
+///             Err("quantity must be positive".into())
+///         }
+///     }
+/// }
+///
+/// fn ship(order: &Validated<Order>) -> u32 {
+///     order.quantity
+/// }
+///
+/// let order: Validated<Order> = serde_json::from_str(r#"{ "quantity": 2 }"#).unwrap();
+/// assert_eq!(ship(&order), 2);
+///
+/// assert!(serde_json::from_str::<Validated<Order>>(r#"{ "quantity": 0 }"#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Validated<T: Validate>(T);
+
+impl<T: Validate> Validated<T> {
+    /// Validates `value`, wrapping it if valid.
+    pub fn new(value: T) -> Result<Self, T::Error> {
+        value.validated().map(Validated)
+    }
+
+    /// Returns the validated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// Mutates the value with `f` and validates it again.
+    ///
+    /// If the mutated value is not valid, the mutation is rolled back and the error is returned,
+    /// so the wrapper always holds a valid value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_validate::{Validate, Validated};
+    ///
+    /// #[derive(Clone)]
+    /// struct Stock(i32);
+    ///
+    /// impl Validate for Stock {
+    ///     type Error = String;
+    ///
+    ///     fn validate(&self) -> Result<(), Self::Error> {
+    ///         if self.0 >= 0 {
+    ///             Ok(())
+    ///         } else {
+    ///             Err("stock cannot be negative".into())
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut stock = Validated::new(Stock(1)).unwrap();
+    /// assert!(stock.modify(|s| s.0 -= 1).is_ok());
+    /// assert!(stock.modify(|s| s.0 -= 1).is_err());
+    /// assert_eq!(stock.0, 0);
+    /// ```
+    pub fn modify<F>(&mut self, f: F) -> Result<(), T::Error>
+    where
+        T: Clone,
+        F: FnOnce(&mut T),
+    {
+        let snapshot = self.0.clone();
+        f(&mut self.0);
+        self.0.validate().inspect_err(|_| self.0 = snapshot)
+    }
+
+    /// Consumes the wrapper, mutating the value with `f` and validating it again.
+    ///
+    /// Unlike [`Validated::modify`], this does not need a snapshot to roll back to, so `T` does
+    /// not need to be `Clone`. If the mutated value is not valid, it is returned unwrapped along
+    /// with the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_validate::{Validate, Validated};
+    ///
+    /// #[derive(Debug)]
+    /// struct Stock(i32);
+    ///
+    /// impl Validate for Stock {
+    ///     type Error = String;
+    ///
+    ///     fn validate(&self) -> Result<(), Self::Error> {
+    ///         if self.0 >= 0 {
+    ///             Ok(())
+    ///         } else {
+    ///             Err("stock cannot be negative".into())
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let stock = Validated::new(Stock(1)).unwrap();
+    /// let stock = stock.into_modified(|s| s.0 -= 1).unwrap();
+    /// let (invalid, err) = stock.into_modified(|s| s.0 -= 1).unwrap_err();
+    /// assert_eq!((invalid.0, err.as_str()), (-1, "stock cannot be negative"));
+    /// ```
+    pub fn into_modified<F>(mut self, f: F) -> Result<Self, (T, T::Error)>
+    where
+        F: FnOnce(&mut T),
+    {
+        f(&mut self.0);
+        match self.0.validate() {
+            Ok(()) => Ok(self),
+            Err(err) => Err((self.0, err)),
+        }
+    }
+
+    /// Builds a new validated value from a copy of this one, leaving this one untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_validate::{Validate, Validated};
+    ///
+    /// #[derive(Clone)]
+    /// struct Stock(i32);
+    ///
+    /// impl Validate for Stock {
+    ///     type Error = String;
+    ///
+    ///     fn validate(&self) -> Result<(), Self::Error> {
+    ///         if self.0 >= 0 {
+    ///             Ok(())
+    ///         } else {
+    ///             Err("stock cannot be negative".into())
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let stock = Validated::new(Stock(1)).unwrap();
+    /// let restocked = stock.try_map(|s| Stock(s.0 + 10)).unwrap();
+    /// assert!(stock.try_map(|s| Stock(s.0 - 10)).is_err());
+    /// assert_eq!((stock.0, restocked.0), (1, 11));
+    /// ```
+    pub fn try_map<U, F>(&self, f: F) -> Result<Validated<U>, U::Error>
+    where
+        T: Clone,
+        U: Validate,
+        F: FnOnce(T) -> U,
+    {
+        Validated::new(f(self.0.clone()))
+    }
+}
+
+impl<T: Validate> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Validate> AsRef<T> for Validated<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Validate + Display> Display for Validated<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Validate + serde::Serialize> serde::Serialize for Validated<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Validated<T>
+where
+    T: Validate + serde::Deserialize<'de>,
+    T::Error: Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::de::deserialize_validated(deserializer).map(Validated)
+    }
+}
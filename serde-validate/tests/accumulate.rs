@@ -0,0 +1,106 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_validate::{
+    from_deserializer, validate_deser, DeserializeError, Validate, ValidationError,
+    ValidationErrors,
+};
+
+#[derive(Deserialize)]
+struct Item {
+    email: String,
+    quantity: u32,
+}
+
+impl Validate for Item {
+    type Error = ValidationErrors;
+    fn validate(&self) -> Result<(), Self::Error> {
+        let mut errors = ValidationErrors::new();
+        if !self.email.contains('@') {
+            errors.push(ValidationError::new("email").at("email"));
+        }
+        if self.quantity == 0 {
+            errors.push(ValidationError::new("range").at("quantity"));
+        }
+        errors.into_result()
+    }
+}
+
+#[validate_deser]
+struct Order {
+    name: String,
+    items: Vec<Item>,
+}
+
+impl Validate for Order {
+    type Error = ValidationErrors;
+    fn validate(&self) -> Result<(), Self::Error> {
+        let mut errors = ValidationErrors::new();
+        if self.name.is_empty() {
+            errors.push(ValidationError::new("length").at("name"));
+        }
+        let mut items = ValidationErrors::new();
+        for (i, item) in self.items.iter().enumerate() {
+            items.nest(i, item.validate());
+        }
+        errors.extend_at("items", items);
+        errors.into_result()
+    }
+}
+
+const INVALID: &str = r#"{
+    "name": "",
+    "items": [
+        { "email": "a@b.c", "quantity": 1 },
+        { "email": "a@b.c", "quantity": 0 },
+        { "email": "nope", "quantity": 0 }
+    ]
+}"#;
+
+#[test]
+fn test_collects_all_errors() {
+    let errors =
+        match from_deserializer::<Order, _>(&mut serde_json::Deserializer::from_str(INVALID)) {
+            Err(DeserializeError::Validate(errors)) => errors,
+            _ => panic!("expected validation errors"),
+        };
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors.at_path("name").count(), 1);
+    assert_eq!(errors.at_path("items[1].quantity").count(), 1);
+    assert_eq!(errors.at_path("items[2].email").count(), 1);
+    assert_eq!(errors.at_path("items[2].quantity").count(), 1);
+    assert_eq!(errors.by_path().len(), 4);
+}
+
+#[test]
+fn test_message_contains_whole_report() {
+    let err = serde_json::from_str::<Order>(INVALID).err().unwrap();
+    assert!(err.to_string().starts_with(
+        "name: length; items[1].quantity: range; items[2].email: email; items[2].quantity: range"
+    ));
+}
+
+#[test]
+fn test_valid() {
+    assert!(serde_json::from_str::<Order>(
+        r#"{ "name": "order", "items": [{ "email": "a@b.c", "quantity": 1 }] }"#
+    )
+    .is_ok());
+}
@@ -0,0 +1,165 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_validate::{AsyncValidate, Pending, ValidationError};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::task::{Context, Poll, Waker};
+
+thread_local! {
+    static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn log(entry: String) {
+    LOG.with(|log| log.borrow_mut().push(entry));
+}
+
+fn take_log() -> Vec<String> {
+    LOG.with(|log| log.take())
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Stands in for a uniqueness lookup in a database.
+async fn unique(name: &String) -> Result<(), ValidationError> {
+    log(format!("start {name}"));
+    YieldNow(false).await;
+    log(format!("end {name}"));
+    if name == "taken" {
+        Err(ValidationError::new("unique"))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, AsyncValidate)]
+struct Account {
+    #[validate(length(min = 1), custom_async = unique)]
+    name: String,
+    #[validate(custom_async = unique)]
+    alias: String,
+}
+
+#[derive(Deserialize, AsyncValidate)]
+struct Team {
+    #[validate(nested_async)]
+    owner: Account,
+    #[validate(nested_async)]
+    members: Vec<Account>,
+}
+
+#[test]
+fn test_async_ok() {
+    let account = Account {
+        name: "a".into(),
+        alias: "b".into(),
+    };
+    assert!(block_on(account.validate_async()).is_ok());
+    take_log();
+}
+
+#[test]
+fn test_async_checks_run_concurrently() {
+    let account = Account {
+        name: "a".into(),
+        alias: "b".into(),
+    };
+    block_on(account.validate_async()).unwrap();
+    assert_eq!(take_log(), vec!["start a", "start b", "end a", "end b"]);
+}
+
+#[test]
+fn test_async_errors() {
+    let account = Account {
+        name: String::new(),
+        alias: "taken".into(),
+    };
+    let errors = block_on(account.validate_async()).unwrap_err();
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|e| format!("{}:{}", e.path(), e.code()))
+        .collect();
+    assert_eq!(errors, vec!["name:length", "alias:unique"]);
+    take_log();
+}
+
+#[test]
+fn test_nested_async() {
+    let team: Pending<Team> = serde_json::from_str(
+        r#"{
+            "owner": { "name": "a", "alias": "b" },
+            "members": [{ "name": "c", "alias": "d" }, { "name": "taken", "alias": "e" }]
+        }"#,
+    )
+    .unwrap();
+    let errors = block_on(team.validate()).err().unwrap();
+    assert_eq!(errors.to_string(), "members[1].name: unique");
+    take_log();
+}
+
+#[test]
+fn test_pending_validated() {
+    let pending: Pending<Account> =
+        serde_json::from_str(r#"{ "name": "a", "alias": "b" }"#).unwrap();
+    assert_eq!(pending.get_unvalidated().name, "a");
+    let account = block_on(pending.validate()).unwrap();
+    assert_eq!(account.alias, "b");
+    take_log();
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+#[test]
+fn test_futures_are_send() {
+    let team = Team {
+        owner: Account {
+            name: "a".into(),
+            alias: "b".into(),
+        },
+        members: Vec::new(),
+    };
+    assert_send(team.validate_async());
+    assert_send(team.members.validate_async());
+    assert_send(Pending::new(team).validate());
+}
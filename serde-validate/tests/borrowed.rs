@@ -0,0 +1,140 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::DeserializeSeed;
+use serde_validate::de::WithContext;
+use serde_validate::{validate_deser, Validate, ValidateWith};
+use std::borrow::Cow;
+
+#[validate_deser]
+#[derive(Debug)]
+struct Message<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    body: Cow<'a, str>,
+}
+
+impl Validate for Message<'_> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.name.is_empty() {
+            Err("name cannot be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_borrows_from_input() {
+    let input = r#"{ "name": "greeting", "body": "hello" }"#.to_string();
+    let message: Message = serde_json::from_str(&input).unwrap();
+    assert_eq!(message.name, "greeting");
+    assert!(matches!(message.body, Cow::Borrowed("hello")));
+}
+
+#[test]
+fn test_borrowed_still_validates() {
+    assert!(serde_json::from_str::<Message>(r#"{ "name": "", "body": "hello" }"#).is_err());
+}
+
+#[validate_deser]
+#[derive(Debug)]
+enum Token<'a, T> {
+    Word(&'a str),
+    Value(T),
+}
+
+impl<T> Validate for Token<'_, T> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Token::Word(word) if word.contains(' ') => Err("word has spaces".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_borrowed_generic_enum() {
+    let token: Token<i32> = serde_json::from_str(r#"{ "Word": "hi" }"#).unwrap();
+    assert!(matches!(token, Token::Word("hi")));
+    assert!(serde_json::from_str::<Token<i32>>(r#"{ "Word": "h i" }"#).is_err());
+}
+
+#[validate_deser(context = usize)]
+struct Line<'a>(&'a str);
+
+impl ValidateWith<usize> for Line<'_> {
+    type Error = String;
+
+    fn validate_with(&self, max: &usize) -> Result<(), Self::Error> {
+        if self.0.len() <= *max {
+            Ok(())
+        } else {
+            Err("line too long".to_string())
+        }
+    }
+}
+
+#[test]
+fn test_borrowed_with_context() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#""abc""#);
+    let line = WithContext::<Line, _>::new(&3)
+        .deserialize(&mut deserializer)
+        .unwrap();
+    assert_eq!(line.0, "abc");
+    let mut deserializer = serde_json::Deserializer::from_str(r#""abcd""#);
+    assert!(WithContext::<Line, _>::new(&3)
+        .deserialize(&mut deserializer)
+        .is_err());
+}
+
+#[validate_deser]
+#[derive(Debug)]
+struct Label<'a> {
+    text: &'a str,
+}
+
+impl<'a> Validate for Label<'a> {
+    type Error = &'a str;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self.text {
+            "" => Err("text cannot be empty"),
+            text if text.starts_with(' ') => Err(text),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_borrowing_error_type() {
+    let input = r#"{ "text": " padded" }"#.to_string();
+    let err = serde_json::from_str::<Label>(&input).unwrap_err();
+    assert!(err.to_string().starts_with(" padded"));
+    assert_eq!(
+        serde_json::from_str::<Label>(r#"{ "text": "ok" }"#)
+            .unwrap()
+            .text,
+        "ok"
+    );
+}
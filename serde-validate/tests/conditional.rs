@@ -0,0 +1,192 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{validate_deser, Validate, ValidationError};
+
+const EU: [&str; 3] = ["DE", "FR", "NL"];
+
+fn in_eu(company: &Company) -> bool {
+    EU.contains(&company.country.as_str())
+}
+
+fn is_registered(company: &Company) -> bool {
+    company.registration.is_some()
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+#[validate(required_unless(field = tax_number, when = is_registered))]
+#[validate(exactly_one_of(email, phone))]
+struct Company {
+    country: String,
+    #[validate(required, when = in_eu)]
+    #[validate(length(min = 8))]
+    vat_id: Option<String>,
+    registration: Option<String>,
+    tax_number: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+}
+
+fn company(country: &str, vat_id: Option<&str>) -> Company {
+    Company {
+        country: country.into(),
+        vat_id: vat_id.map(Into::into),
+        registration: Some("HRB 1".into()),
+        tax_number: None,
+        email: Some("a@example.com".into()),
+        phone: None,
+    }
+}
+
+#[test]
+fn test_conditional_rule_applies_when_condition_holds() {
+    assert!(company("US", None).validate().is_ok());
+    assert_eq!(
+        company("DE", None).validate().unwrap_err().to_string(),
+        "vat_id: is required"
+    );
+    assert!(company("DE", Some("DE123456")).validate().is_ok());
+}
+
+#[test]
+fn test_unconditional_rules_still_apply() {
+    assert_eq!(
+        company("US", Some("DE1"))
+            .validate()
+            .unwrap_err()
+            .to_string(),
+        "vat_id: length must be at least 8"
+    );
+}
+
+#[test]
+fn test_required_unless() {
+    let mut company = company("US", None);
+    company.registration = None;
+    let errors = company.validate().unwrap_err();
+    assert_eq!(errors.to_string(), "tax_number: is required");
+    assert_eq!(errors.iter().next().unwrap().code(), "required");
+    company.tax_number = Some("123".into());
+    assert!(company.validate().is_ok());
+}
+
+#[test]
+fn test_exactly_one_of() {
+    let mut company = company("US", None);
+    company.phone = Some("555".into());
+    let errors = company.validate().unwrap_err();
+    assert_eq!(errors.at_path("email").count(), 1);
+    assert_eq!(errors.at_path("phone").count(), 1);
+    company.email = None;
+    company.phone = None;
+    let errors = company.validate().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors.iter().next().unwrap().to_string(),
+        "email: exactly one of email, phone must be set"
+    );
+}
+
+#[test]
+fn test_conditional_rules_checked_after_deserializing() {
+    let input = r#"{ "country": "FR", "registration": "RCS 1", "email": "a@example.com" }"#;
+    let err = serde_json::from_str::<Company>(input).unwrap_err();
+    assert!(err.to_string().starts_with("vat_id: is required"));
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Point,
+    Range,
+}
+
+fn is_range(period: &Period) -> bool {
+    period.kind == Kind::Range
+}
+
+fn ends_after_start(period: &Period) -> Result<(), ValidationError> {
+    match period.end {
+        Some(end) if end <= period.start => {
+            Err(ValidationError::new("period").with_message("must end after its start"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Validate)]
+#[validate(required_if(field = end, when = is_range))]
+#[validate(custom = ends_after_start, when = is_range)]
+struct Period {
+    kind: Kind,
+    start: u32,
+    #[validate(range(min = 1), when = is_range)]
+    end: Option<u32>,
+}
+
+#[test]
+fn test_required_if() {
+    let period = Period {
+        kind: Kind::Range,
+        start: 0,
+        end: None,
+    };
+    assert_eq!(
+        period.validate().unwrap_err().to_string(),
+        "end: is required"
+    );
+    let period = Period {
+        kind: Kind::Point,
+        start: 0,
+        end: Some(0),
+    };
+    assert!(period.validate().is_ok());
+    let period = Period {
+        kind: Kind::Range,
+        start: 0,
+        end: Some(0),
+    };
+    assert_eq!(
+        period.validate().unwrap_err().to_string(),
+        "end: must be at least 1; must end after its start"
+    );
+}
+
+fn is_card(payment: &Payment) -> bool {
+    matches!(payment, Payment::Card { .. })
+}
+
+#[derive(Debug, Validate)]
+enum Payment {
+    Card {
+        #[validate(required, when = is_card)]
+        holder: Option<String>,
+    },
+    Cash,
+}
+
+#[test]
+fn test_enum_conditional_rule() {
+    assert!(Payment::Cash.validate().is_ok());
+    let payment = Payment::Card { holder: None };
+    assert_eq!(
+        payment.validate().unwrap_err().to_string(),
+        "holder: is required"
+    );
+}
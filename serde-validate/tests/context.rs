@@ -0,0 +1,103 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::DeserializeSeed;
+use serde_validate::de::WithContext;
+use serde_validate::{
+    from_deserializer_with, validate_deser, DeserializeError, ValidateWith, ValidationError,
+};
+use std::collections::HashSet;
+
+struct Tenants(HashSet<&'static str>);
+
+#[validate_deser(context = Tenants)]
+#[derive(Debug)]
+struct Request {
+    tenant: String,
+}
+
+impl ValidateWith<Tenants> for Request {
+    type Error = ValidationError;
+    fn validate_with(&self, tenants: &Tenants) -> Result<(), Self::Error> {
+        if tenants.0.contains(self.tenant.as_str()) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("unknown_tenant").at("tenant"))
+        }
+    }
+}
+
+#[validate_deser(context = usize)]
+#[derive(Debug)]
+struct Batch<T> {
+    items: Vec<T>,
+}
+
+impl<T> ValidateWith<usize> for Batch<T> {
+    type Error = String;
+    fn validate_with(&self, max: &usize) -> Result<(), Self::Error> {
+        if self.items.len() <= *max {
+            Ok(())
+        } else {
+            Err(format!("at most {max} items"))
+        }
+    }
+}
+
+fn tenants() -> Tenants {
+    Tenants(HashSet::from(["acme"]))
+}
+
+#[test]
+fn test_seed_ok() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "tenant": "acme" }"#);
+    let request = WithContext::<Request, _>::new(&tenants())
+        .deserialize(&mut deserializer)
+        .unwrap();
+    assert_eq!(request.tenant, "acme");
+}
+
+#[test]
+fn test_seed_invalid() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "tenant": "other" }"#);
+    assert!(WithContext::<Request, _>::new(&tenants())
+        .deserialize(&mut deserializer)
+        .is_err());
+}
+
+#[test]
+fn test_from_deserializer_with() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "tenant": "other" }"#);
+    match from_deserializer_with::<Request, _, _>(&mut deserializer, &tenants()) {
+        Err(DeserializeError::Validate(err)) => assert_eq!(err.code(), "unknown_tenant"),
+        _ => panic!("expected a validation error"),
+    }
+}
+
+#[test]
+fn test_generic() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "items": [1, 2, 3] }"#);
+    assert!(WithContext::<Batch<u8>, _>::new(&3)
+        .deserialize(&mut deserializer)
+        .is_ok());
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "items": [1, 2, 3] }"#);
+    assert!(WithContext::<Batch<u8>, _>::new(&2)
+        .deserialize(&mut deserializer)
+        .is_err());
+}
@@ -0,0 +1,89 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+mod facade {
+    pub mod validation {
+        pub use serde_validate::*;
+    }
+}
+
+mod shadowed {
+    // The generated code must not pick up these local items.
+    #[allow(dead_code)]
+    mod serde {}
+    #[allow(dead_code)]
+    mod serde_validate {}
+    #[allow(dead_code)]
+    type Result = ();
+    #[allow(dead_code)]
+    struct Vec;
+    #[allow(dead_code)]
+    struct Option;
+
+    use crate::facade::validation::{validate_serde, Validate};
+
+    #[validate_serde(crate = "crate::facade::validation")]
+    #[derive(Validate)]
+    #[validate(crate = "crate::facade::validation")]
+    pub struct Percentage {
+        #[validate(range(min = 0, max = 100))]
+        pub value: i32,
+        #[validate(length(max = 3))]
+        pub unit: ::core::option::Option<String>,
+    }
+}
+
+mod explicit_serde {
+    use serde_validate::{validate_deser, Validate};
+
+    #[validate_deser(crate = ::serde_validate, serde = ::serde)]
+    pub struct Even(pub i32);
+
+    impl Validate for Even {
+        type Error = String;
+
+        fn validate(&self) -> Result<(), Self::Error> {
+            if self.0 % 2 == 0 {
+                Ok(())
+            } else {
+                Err("must be even".to_string())
+            }
+        }
+    }
+}
+
+use explicit_serde::Even;
+use shadowed::Percentage;
+
+#[test]
+fn test_through_facade() {
+    let percentage: Percentage = serde_json::from_str(r#"{ "value": 50, "unit": "%" }"#).unwrap();
+    assert_eq!(percentage.value, 50);
+    assert!(serde_json::from_str::<Percentage>(r#"{ "value": 150, "unit": null }"#).is_err());
+    assert_eq!(
+        serde_json::to_string(&percentage).unwrap(),
+        r#"{"value":50,"unit":"%"}"#
+    );
+}
+
+#[test]
+fn test_explicit_serde_path() {
+    assert_eq!(serde_json::from_str::<Even>("2").unwrap().0, 2);
+    assert!(serde_json::from_str::<Even>("3").is_err());
+}
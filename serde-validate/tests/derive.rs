@@ -0,0 +1,157 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{validate_deser, Validate, ValidationError, ValidationErrors};
+
+fn not_admin(name: &str) -> Result<(), ValidationError> {
+    if name == "admin" {
+        Err(ValidationError::new("reserved"))
+    } else {
+        Ok(())
+    }
+}
+
+fn ordered(range: &Range) -> Result<(), ValidationError> {
+    if range.start <= range.end {
+        Ok(())
+    } else {
+        Err(ValidationError::new("ordered").at("end"))
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct User {
+    #[validate(length(min = 1, max = 8), custom = not_admin)]
+    name: String,
+    #[validate(range(min = 0, max = 150))]
+    age: i32,
+    #[validate(length(max = 2))]
+    nickname: Option<String>,
+}
+
+#[derive(Validate)]
+#[validate(custom = ordered)]
+struct Range {
+    #[validate(range(min = 0.0))]
+    start: f64,
+    end: f64,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Pair(
+    #[validate(range(min = 1))] u8,
+    #[validate(length(min = 1))] Vec<u8>,
+);
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+enum Shape {
+    Circle {
+        #[validate(range(min = 0.0))]
+        radius: f64,
+    },
+    Polygon(#[validate(length(min = 3))] Vec<(i32, i32)>),
+    Empty,
+}
+
+fn errors<T: Validate<Error = ValidationErrors>>(value: &T) -> Vec<String> {
+    match value.validate() {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .iter()
+            .map(|e| format!("{}:{}", e.path(), e.code()))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_named_ok() {
+    assert!(
+        serde_json::from_str::<User>(r#"{ "name": "Lucas", "age": 30, "nickname": null }"#).is_ok()
+    );
+}
+
+#[test]
+fn test_named_collects_errors() {
+    let user = User {
+        name: String::new(),
+        age: 200,
+        nickname: Some("abc".into()),
+    };
+    assert_eq!(
+        errors(&user),
+        vec!["name:length", "age:range", "nickname:length"]
+    );
+}
+
+#[test]
+fn test_named_custom() {
+    let user = User {
+        name: "admin".into(),
+        age: 20,
+        nickname: None,
+    };
+    assert_eq!(errors(&user), vec!["name:reserved"]);
+}
+
+#[test]
+fn test_named_deserialize_invalid() {
+    assert!(
+        serde_json::from_str::<User>(r#"{ "name": "", "age": 30, "nickname": null }"#).is_err()
+    );
+}
+
+#[test]
+fn test_container_custom() {
+    let range = Range {
+        start: -1.0,
+        end: -2.0,
+    };
+    assert_eq!(errors(&range), vec!["start:range", "end:ordered"]);
+}
+
+#[test]
+fn test_range_rejects_nan() {
+    let range = Range {
+        start: f64::NAN,
+        end: f64::NAN,
+    };
+    assert_eq!(errors(&range), vec!["start:range", "end:ordered"]);
+}
+
+#[test]
+fn test_unnamed() {
+    assert_eq!(errors(&Pair(0, vec![])), vec!["[0]:range", "[1]:length"]);
+    assert!(serde_json::from_str::<Pair>("[1, [1]]").is_ok());
+    assert!(serde_json::from_str::<Pair>("[0, [1]]").is_err());
+}
+
+#[test]
+fn test_enum() {
+    assert_eq!(
+        errors(&Shape::Circle { radius: -1.0 }),
+        vec!["radius:range"]
+    );
+    assert_eq!(errors(&Shape::Polygon(vec![(0, 0)])), vec!["[0]:length"]);
+    assert!(errors(&Shape::Empty).is_empty());
+    assert!(serde_json::from_str::<Shape>(r#"{ "Circle": { "radius": 1.0 } }"#).is_ok());
+    assert!(serde_json::from_str::<Shape>(r#"{ "Polygon": [[0, 0]] }"#).is_err());
+}
@@ -0,0 +1,209 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{
+    from_deserializer, from_deserializer_with_warnings, validate_deser, DeserializeError,
+    PathSegment, Validate, ValidationError, ValidationErrors,
+};
+
+#[validate_deser]
+#[derive(Debug)]
+struct Percentage {
+    value: i32,
+}
+
+impl Validate for Percentage {
+    type Error = ValidationError;
+    fn validate(&self) -> Result<(), Self::Error> {
+        if (0..=100).contains(&self.value) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("range")
+                .with_message("value must be between 0 and 100")
+                .with_param("min", 0)
+                .with_param("max", 100)
+                .at("value"))
+        }
+    }
+}
+
+fn from_str(
+    input: &str,
+) -> Result<Percentage, DeserializeError<serde_json::Error, ValidationError>> {
+    from_deserializer(&mut serde_json::Deserializer::from_str(input))
+}
+
+#[test]
+fn test_deserialize_ok() {
+    assert_eq!(from_str("{ \"value\": 50 }").unwrap().value, 50);
+}
+
+#[test]
+fn test_deserialize_invalid() {
+    match from_str("{ \"value\": 150 }") {
+        Err(DeserializeError::Validate(err)) => {
+            assert_eq!(err.code(), "range");
+            assert_eq!(err.message(), Some("value must be between 0 and 100"));
+            assert_eq!(err.params().get("max").map(String::as_str), Some("100"));
+            assert_eq!(err.path().segments(), &[PathSegment::from("value")]);
+        }
+        _ => panic!("expected a validation error"),
+    }
+}
+
+#[test]
+fn test_deserialize_malformed() {
+    assert!(matches!(
+        from_str("{ \"value\": \"50\" }"),
+        Err(DeserializeError::Deserialize(_))
+    ));
+}
+
+#[test]
+fn test_custom_error_message() {
+    let err = serde_json::from_str::<Percentage>("{ \"value\": -1 }").unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("value: value must be between 0 and 100"));
+}
+
+#[test]
+fn test_path_display() {
+    let err = ValidationError::new("email")
+        .at("email")
+        .at(3)
+        .at("items")
+        .at(PathSegment::key("en"));
+    assert_eq!(err.path().to_string(), "[\"en\"].items[3].email");
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Inner {
+    #[validate(range(min = 1))]
+    x: i32,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Outer {
+    #[validate(length(min = 1))]
+    name: String,
+    inner: Inner,
+}
+
+fn outer_from_str(
+    input: &str,
+) -> Result<Outer, DeserializeError<serde_json::Error, ValidationErrors>> {
+    from_deserializer(&mut serde_json::Deserializer::from_str(input))
+}
+
+#[test]
+fn test_nested_ok() {
+    let outer = outer_from_str("{ \"name\": \"a\", \"inner\": { \"x\": 2 } }").unwrap();
+    assert_eq!(outer.inner.x, 2);
+}
+
+#[test]
+fn test_nested_failure_is_deserialize_error() {
+    match outer_from_str("{ \"name\": \"a\", \"inner\": { \"x\": 0 } }") {
+        Err(DeserializeError::Deserialize(err)) => {
+            assert!(err.to_string().starts_with("x: "));
+        }
+        other => panic!("expected a deserialize error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_outer_failure_is_validate_error() {
+    match outer_from_str("{ \"name\": \"\", \"inner\": { \"x\": 1 } }") {
+        Err(DeserializeError::Validate(errors)) => {
+            let paths: Vec<String> = errors.iter().map(|e| e.path().to_string()).collect();
+            assert_eq!(paths, vec!["name"]);
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_sequence_element_failure_is_deserialize_error() {
+    let result: Result<Vec<Inner>, DeserializeError<serde_json::Error, ValidationErrors>> =
+        from_deserializer(&mut serde_json::Deserializer::from_str(
+            "[{ \"x\": 1 }, { \"x\": 0 }]",
+        ));
+    assert!(matches!(result, Err(DeserializeError::Deserialize(_))));
+}
+
+#[derive(Debug, serde::Deserialize, Validate)]
+struct Plain {
+    #[validate(range(min = 0))]
+    v: i32,
+}
+
+#[test]
+fn test_plain_deserialize_is_validated() {
+    let result: Result<Plain, DeserializeError<serde_json::Error, ValidationErrors>> =
+        from_deserializer(&mut serde_json::Deserializer::from_str("{ \"v\": -1 }"));
+    match result {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.at_path("v").count(), 1);
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+    let result = from_deserializer_with_warnings::<Plain, _>(
+        &mut serde_json::Deserializer::from_str("{ \"v\": -1 }"),
+    );
+    assert!(matches!(result, Err(DeserializeError::Validate(_))));
+}
+
+fn positive(value: &i32) -> Result<(), ValidationError> {
+    if *value > 0 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+#[validate_deser]
+#[derive(Debug)]
+struct Shape {
+    #[validate(field = positive)]
+    w: i32,
+    h: i32,
+}
+
+impl Validate for Shape {
+    type Error = ValidationErrors;
+    fn validate(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_field_checks_run_for_the_target() {
+    let input = "{ \"w\": -1, \"h\": \"not a number\" }";
+    match from_deserializer::<Shape, _>(&mut serde_json::Deserializer::from_str(input)) {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.to_string(), "w: positive");
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+    let shape: Shape = serde_json::from_str("{ \"w\": 1, \"h\": 2 }").unwrap();
+    assert_eq!((shape.w, shape.h), (1, 2));
+}
@@ -0,0 +1,236 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Deserializer};
+use serde_validate::{
+    from_deserializer, validate_deser, validate_serde, DeserializeError, Validate, ValidationError,
+};
+
+fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        Err(ValidationError::new("blank").with_message("must not be blank"))
+    } else {
+        Ok(())
+    }
+}
+
+fn positive(value: &i32) -> Result<(), ValidationError> {
+    if *value > 0 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive").with_message("must be positive"))
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct User {
+    #[validate(field = not_blank)]
+    name: String,
+    #[validate(field = positive)]
+    age: i32,
+    #[validate(field = not_blank)]
+    nickname: Option<String>,
+}
+
+#[test]
+fn test_field_check_passes() {
+    let user: User =
+        serde_json::from_str(r#"{ "name": "Ann", "age": 30, "nickname": null }"#).unwrap();
+    assert_eq!(user.name, "Ann");
+    assert_eq!(user.age, 30);
+    assert_eq!(user.nickname, None);
+}
+
+#[test]
+fn test_field_check_reports_position_of_value() {
+    let input = "{\n  \"name\": \" \",\n  \"age\": 30,\n  \"nickname\": null\n}";
+    let err = serde_json::from_str::<User>(input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "name: must not be blank at line 2 column 13"
+    );
+    assert_eq!((err.line(), err.column()), (2, 13));
+}
+
+#[test]
+fn test_field_check_fails_before_later_fields() {
+    let err = serde_json::from_str::<User>(r#"{ "age": 0, "name": 42 }"#).unwrap_err();
+    assert_eq!(err.to_string(), "age: must be positive at line 1 column 10");
+}
+
+#[test]
+fn test_field_check_on_present_option() {
+    let err = serde_json::from_str::<User>(r#"{ "name": "Ann", "age": 30, "nickname": "" }"#)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "nickname: must not be blank at line 1 column 44"
+    );
+}
+
+#[test]
+fn test_field_check_on_missing_option() {
+    let user: User = serde_json::from_str(r#"{ "name": "Ann", "age": 30 }"#).unwrap();
+    assert_eq!(user.nickname, None);
+}
+
+fn trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer).map(|value| value.map(|s| s.trim().to_owned()))
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Alias {
+    #[validate(field = not_blank)]
+    #[serde(deserialize_with = "trimmed")]
+    alias: Option<String>,
+}
+
+#[test]
+fn test_field_check_on_missing_option_with_function() {
+    let alias: Alias = serde_json::from_str(r#"{ "alias": " a " }"#).unwrap();
+    assert_eq!(alias.alias.as_deref(), Some("a"));
+    let err = serde_json::from_str::<Alias>("{}").unwrap_err();
+    assert!(err.to_string().starts_with("missing field `alias`"));
+}
+
+#[test]
+fn test_field_rule_checked_by_derive() {
+    let user = User {
+        name: "Ann".into(),
+        age: -1,
+        nickname: None,
+    };
+    assert_eq!(
+        user.validate().unwrap_err().to_string(),
+        "age: must be positive"
+    );
+}
+
+#[test]
+fn test_field_check_keeps_typed_error() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "name": "", "age": 1 }"#);
+    match from_deserializer::<User, _>(&mut deserializer) {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors.at_path("name").count(), 1);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[validate_deser]
+#[derive(Debug)]
+enum Shape {
+    Circle(#[validate(field = positive)] i32),
+    Rect {
+        #[validate(field = positive)]
+        width: i32,
+        #[validate(field = positive)]
+        height: i32,
+    },
+}
+
+impl Validate for Shape {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_field_check_in_enum() {
+    let shape: Shape = serde_json::from_str(r#"{ "Rect": { "width": 1, "height": 2 } }"#).unwrap();
+    assert!(matches!(
+        shape,
+        Shape::Rect {
+            width: 1,
+            height: 2
+        }
+    ));
+    let shape: Shape = serde_json::from_str(r#"{ "Circle": 1 }"#).unwrap();
+    assert!(matches!(shape, Shape::Circle(1)));
+    let err = serde_json::from_str::<Shape>(r#"{ "Circle": 0 }"#).unwrap_err();
+    assert_eq!(err.to_string(), "[0]: must be positive");
+    let err =
+        serde_json::from_str::<Shape>(r#"{ "Rect": { "width": 1, "height": -2 } }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "height: must be positive at line 1 column 38"
+    );
+}
+
+mod trimmed {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        Ok(String::deserialize(deserializer)?.trim().to_string())
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Tag<T> {
+    #[serde(deserialize_with = "trimmed::deserialize")]
+    #[validate(field = not_blank)]
+    name: String,
+    #[validate(field = not_empty)]
+    values: Vec<T>,
+}
+
+fn not_empty<T>(value: &[T]) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        Err(ValidationError::new("empty"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_field_check_with_generics_and_deserialize_with() {
+    let tag: Tag<u8> = serde_json::from_str(r#"{ "name": " a ", "values": [1] }"#).unwrap();
+    assert_eq!(tag.name, "a");
+    let err = serde_json::from_str::<Tag<u8>>(r#"{ "name": " ", "values": [1] }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "name: must not be blank at line 1 column 13"
+    );
+    let err = serde_json::from_str::<Tag<u8>>(r#"{ "name": "a", "values": [] }"#).unwrap_err();
+    assert_eq!(err.to_string(), "values: empty at line 1 column 29");
+}
+
+#[validate_serde]
+#[derive(Debug, Validate)]
+struct Label {
+    #[validate(field = not_blank)]
+    text: String,
+}
+
+#[test]
+fn test_field_check_with_validate_serde() {
+    let label: Label = serde_json::from_str(r#"{ "text": "a" }"#).unwrap();
+    assert_eq!(serde_json::to_string(&label).unwrap(), r#"{"text":"a"}"#);
+    let err = serde_json::from_str::<Label>(r#"{ "text": "" }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "text: must not be blank at line 1 column 14"
+    );
+}
@@ -0,0 +1,166 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{validate_deser, validate_serde, Validate};
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+#[validate_deser]
+#[derive(Debug)]
+struct Wrapper<T = u32> {
+    value: T,
+}
+
+impl<T: PartialEq + Default> Validate for Wrapper<T> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.value == T::default() {
+            Err("value cannot be the default".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_defaulted_type_param() {
+    let wrapper: Wrapper = serde_json::from_str(r#"{ "value": 3 }"#).unwrap();
+    assert_eq!(wrapper.value, 3u32);
+    assert!(serde_json::from_str::<Wrapper<String>>(r#"{ "value": "" }"#).is_err());
+}
+
+#[validate_deser]
+struct Buffer<const N: usize = 4> {
+    bytes: Vec<u8>,
+}
+
+impl<const N: usize> Validate for Buffer<N> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.bytes.len() <= N {
+            Ok(())
+        } else {
+            Err(format!("at most {N} bytes"))
+        }
+    }
+}
+
+#[test]
+fn test_defaulted_const_param() {
+    assert!(serde_json::from_str::<Buffer>("{ \"bytes\": [1, 2, 3, 4] }").is_ok());
+    assert!(serde_json::from_str::<Buffer>("{ \"bytes\": [1, 2, 3, 4, 5] }").is_err());
+    assert!(serde_json::from_str::<Buffer<8>>("{ \"bytes\": [1, 2, 3, 4, 5] }").is_ok());
+}
+
+/// Neither `Serialize` nor `Deserialize`.
+#[derive(Default)]
+struct Marker;
+
+#[validate_serde]
+struct Id<T> {
+    raw: u64,
+    #[serde(skip)]
+    cache: Option<T>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Validate for Id<T> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.raw == 0 {
+            Err("id cannot be zero".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_phantom_and_skipped_params_are_not_bounded() {
+    let id: Id<Marker> = serde_json::from_str(r#"{ "raw": 7, "marker": null }"#).unwrap();
+    assert_eq!(id.raw, 7);
+    assert!(id.cache.is_none());
+    assert_eq!(
+        serde_json::to_string(&id).unwrap(),
+        r#"{"raw":7,"marker":null}"#
+    );
+    assert!(serde_json::from_str::<Id<Marker>>(r#"{ "raw": 0, "marker": null }"#).is_err());
+}
+
+trait Unit {
+    type Raw;
+}
+
+struct Meters;
+
+impl Unit for Meters {
+    type Raw = f64;
+}
+
+#[validate_deser]
+struct Measure<U: Unit> {
+    raw: U::Raw,
+}
+
+impl<U: Unit> Validate for Measure<U>
+where
+    U::Raw: PartialOrd + Default,
+{
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.raw >= U::Raw::default() {
+            Ok(())
+        } else {
+            Err("cannot be negative".to_string())
+        }
+    }
+}
+
+#[test]
+fn test_associated_type_is_bounded() {
+    assert!(serde_json::from_str::<Measure<Meters>>(r#"{ "raw": 1.5 }"#).is_ok());
+    assert!(serde_json::from_str::<Measure<Meters>>(r#"{ "raw": -1.5 }"#).is_err());
+}
+
+#[validate_deser(bound = "T: serde::Deserialize<'de> + Display")]
+struct Labeled<T> {
+    label: T,
+}
+
+impl<T: Display> Validate for Labeled<T> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.label.to_string().is_empty() {
+            Err("label cannot be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_bound_override() {
+    assert!(serde_json::from_str::<Labeled<String>>(r#"{ "label": "a" }"#).is_ok());
+    assert!(serde_json::from_str::<Labeled<String>>(r#"{ "label": "" }"#).is_err());
+}
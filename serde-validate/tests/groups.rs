@@ -0,0 +1,148 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::DeserializeSeed;
+use serde_validate::de::WithContext;
+use serde_validate::{validate_deser, validate_serde, Group, Validate, ValidationError};
+
+const CREATE: Group = Group::new("create");
+const UPDATE: Group = Group::new("update");
+const IMPORT: Group = Group::new("import");
+
+fn no_id(order: &Order) -> Result<(), ValidationError> {
+    match order.id {
+        Some(_) => Err(ValidationError::new("id").with_message("must not be set")),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Validate)]
+#[validate(custom = no_id, groups = ["create"])]
+struct Order {
+    id: Option<u64>,
+    #[validate(range(min = 1))]
+    #[validate(range(max = 100), groups = ["create", "update"])]
+    quantity: u32,
+    #[validate(nested)]
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Validate)]
+struct Line {
+    #[validate(length(min = 1), groups = ["create"])]
+    sku: String,
+}
+
+fn order(id: Option<u64>, quantity: u32, sku: &str) -> Order {
+    Order {
+        id,
+        quantity,
+        lines: vec![Line { sku: sku.into() }],
+    }
+}
+
+#[test]
+fn test_ungrouped_rules_always_apply() {
+    let order = order(None, 0, "a");
+    assert!(order.validate().is_err());
+    assert!(order.validate_group(CREATE).is_err());
+    assert!(order.validate_group(IMPORT).is_err());
+}
+
+#[test]
+fn test_grouped_rules_only_apply_to_their_groups() {
+    let order = order(Some(1), 500, "");
+    assert!(order.validate().is_ok());
+    assert!(order.validate_group(IMPORT).is_ok());
+    assert_eq!(
+        order.validate_group(UPDATE).unwrap_err().to_string(),
+        "quantity: must be at most 100"
+    );
+    assert_eq!(
+        order.validate_group(CREATE).unwrap_err().to_string(),
+        "quantity: must be at most 100; lines[0].sku: length must be at least 1; must not be set"
+    );
+}
+
+#[validate_deser(group = "create")]
+#[derive(Debug, Validate)]
+struct NewItem {
+    #[validate(length(max = 3), groups = ["create"])]
+    name: String,
+}
+
+#[test]
+fn test_validate_deser_group() {
+    assert!(serde_json::from_str::<NewItem>(r#"{ "name": "abc" }"#).is_ok());
+    let err = serde_json::from_str::<NewItem>(r#"{ "name": "abcd" }"#).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("name: length must be at most 3"));
+}
+
+#[validate_deser(context = Group)]
+#[derive(Debug, Validate)]
+struct Item {
+    #[validate(length(max = 3), groups = ["create"])]
+    name: String,
+}
+
+#[test]
+fn test_group_picked_by_context_seed() {
+    let input = r#"{ "name": "abcd" }"#;
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    assert!(WithContext::<Item, _>::new(&CREATE)
+        .deserialize(&mut deserializer)
+        .is_err());
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    let item = WithContext::<Item, _>::new(&IMPORT)
+        .deserialize(&mut deserializer)
+        .unwrap();
+    assert_eq!(item.name, "abcd");
+}
+
+#[validate_serde(group = "create")]
+#[derive(Debug, Validate)]
+struct Tag(#[validate(length(min = 1), groups = ["create"])] String);
+
+#[test]
+fn test_validate_serde_group() {
+    assert!(serde_json::to_string(&Tag(String::new())).is_err());
+    assert!(serde_json::from_str::<Tag>(r#""""#).is_err());
+    assert_eq!(serde_json::to_string(&Tag("a".into())).unwrap(), r#""a""#);
+}
+
+#[derive(Debug, Validate)]
+enum Payment {
+    Card {
+        #[validate(length(min = 4, max = 4), groups = ["create"])]
+        last_digits: String,
+    },
+    Cash,
+}
+
+#[test]
+fn test_enum_groups() {
+    let payment = Payment::Card {
+        last_digits: "12".into(),
+    };
+    assert!(payment.validate().is_ok());
+    assert!(payment.validate_group(CREATE).is_err());
+    assert!(Payment::Cash.validate_group(CREATE).is_ok());
+}
@@ -0,0 +1,79 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+mod without_import {
+    #[serde_validate::validate_deser]
+    pub struct Positive(pub i32);
+
+    impl serde_validate::Validate for Positive {
+        type Error = String;
+
+        fn validate(&self) -> Result<(), Self::Error> {
+            if self.0 > 0 {
+                Ok(())
+            } else {
+                Err("must be positive".to_string())
+            }
+        }
+    }
+}
+
+mod inherent_validated {
+    use serde_validate::validate_deser;
+
+    #[validate_deser]
+    #[derive(Debug)]
+    pub struct Name(pub String);
+
+    impl Name {
+        /// An unrelated inherent method that the generated code must not call.
+        #[allow(dead_code)]
+        pub fn validated(self) -> Result<Self, String> {
+            Ok(self)
+        }
+    }
+
+    impl serde_validate::Validate for Name {
+        type Error = String;
+
+        fn validate(&self) -> Result<(), Self::Error> {
+            if self.0.is_empty() {
+                Err("name cannot be empty".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+use inherent_validated::Name;
+use without_import::Positive;
+
+#[test]
+fn test_trait_not_imported() {
+    assert_eq!(serde_json::from_str::<Positive>("1").unwrap().0, 1);
+    assert!(serde_json::from_str::<Positive>("0").is_err());
+}
+
+#[test]
+fn test_inherent_validated_is_ignored() {
+    assert_eq!(serde_json::from_str::<Name>(r#""a""#).unwrap().0, "a");
+    let err = serde_json::from_str::<Name>(r#""""#).unwrap_err();
+    assert_eq!(err.to_string(), "name cannot be empty");
+}
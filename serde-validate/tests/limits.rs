@@ -0,0 +1,166 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_validate::de::Limited;
+use serde_validate::{validate_deser, BoundedString, BoundedVec, Validate};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+thread_local! {
+    static DESERIALIZED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// An element counting how many times it was deserialized.
+#[derive(Debug)]
+struct Counted;
+
+impl<'de> Deserialize<'de> for Counted {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DESERIALIZED.with(|count| count.set(count.get() + 1));
+        u8::deserialize(deserializer).map(|_| Counted)
+    }
+}
+
+fn deserialized() -> usize {
+    DESERIALIZED.with(|count| count.replace(0))
+}
+
+#[test]
+fn test_bounded_string() {
+    let name: BoundedString<3> = serde_json::from_str(r#""héé""#).unwrap();
+    assert_eq!(name.into_inner(), "héé");
+    let err = serde_json::from_str::<BoundedString<3>>(r#""abcd""#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid length 4, expected a string of at most 3 characters at line 1 column 6"
+    );
+    assert!(BoundedString::<3>::new("abcd").is_err());
+    assert_eq!(&*BoundedString::<3>::new("abc").unwrap(), "abc");
+}
+
+#[test]
+fn test_bounded_vec() {
+    deserialized();
+    let values: BoundedVec<Counted, 2> = serde_json::from_str("[1, 2]").unwrap();
+    assert_eq!(values.len(), 2);
+    deserialized();
+
+    let err = serde_json::from_str::<BoundedVec<Counted, 2>>("[1, 2, 3, 4]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "too many items, expected at most 2 at line 1 column 10"
+    );
+    assert_eq!(deserialized(), 2);
+
+    assert!(BoundedVec::<u8, 1>::new(vec![1, 2]).is_err());
+}
+
+#[test]
+fn test_bounded_roundtrip() {
+    let tags: BoundedVec<BoundedString<4>, 2> = serde_json::from_str(r#"["a", "bc"]"#).unwrap();
+    assert_eq!(serde_json::to_string(&tags).unwrap(), r#"["a","bc"]"#);
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Upload {
+    #[validate(max_items = 2)]
+    items: Vec<Counted>,
+    #[validate(max_items = 1)]
+    labels: Option<HashMap<String, String>>,
+}
+
+#[test]
+fn test_max_items_while_deserializing() {
+    deserialized();
+    let upload: Upload =
+        serde_json::from_str(r#"{ "items": [1, 2], "labels": { "a": "b" } }"#).unwrap();
+    assert_eq!(upload.items.len(), 2);
+    deserialized();
+
+    let err = serde_json::from_str::<Upload>(r#"{ "items": [1, 2, 3, 4] }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "too many items, expected at most 2 at line 1 column 21"
+    );
+    assert_eq!(deserialized(), 2);
+
+    let err =
+        serde_json::from_str::<Upload>(r#"{ "items": [], "labels": { "a": "b", "c": "d" } }"#)
+            .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "too many items, expected at most 1 at line 1 column 40"
+    );
+}
+
+#[test]
+fn test_max_items_checked_by_derive() {
+    let upload = Upload {
+        items: vec![Counted, Counted, Counted],
+        labels: None,
+    };
+    assert_eq!(
+        upload.validate().unwrap_err().to_string(),
+        "items: length must be at most 2"
+    );
+}
+
+#[test]
+fn test_limited_depth() {
+    let input = r#"{ "a": [{ "b": [1] }] }"#;
+    let deserialize = |max_depth| {
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        serde_json::Value::deserialize(Limited::new(&mut deserializer).with_max_depth(max_depth))
+    };
+    assert!(deserialize(4).is_ok());
+    assert_eq!(
+        deserialize(3).unwrap_err().to_string(),
+        "nesting too deep, expected at most 3 levels at line 1 column 16"
+    );
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Tree {
+    Leaf(u8),
+    Node(Vec<Tree>),
+    Pair { left: Box<Tree>, right: Box<Tree> },
+}
+
+#[test]
+fn test_limited_depth_through_enums() {
+    let input = r#"{ "Pair": { "left": { "Leaf": 1 }, "right": { "Node": [] } } }"#;
+    let deserialize = |max_depth| {
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        Tree::deserialize(Limited::new(&mut deserializer).with_max_depth(max_depth))
+    };
+    assert!(deserialize(4).is_ok());
+    assert!(deserialize(3).is_err());
+}
+
+#[test]
+fn test_limited_items_of_outermost_only() {
+    let deserialize = |input| {
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        Vec::<Vec<u8>>::deserialize(Limited::new(&mut deserializer).with_max_items(2))
+    };
+    assert!(deserialize("[[1, 2, 3], []]").is_ok());
+    assert!(deserialize("[[], [], []]").is_err());
+}
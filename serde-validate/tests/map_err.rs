@@ -0,0 +1,147 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::{Error, Unexpected};
+use serde_validate::{
+    from_deserializer, validate_deser, DeserializeError, Validate, ValidationError,
+};
+
+#[derive(Debug, PartialEq)]
+enum ColorError {
+    Unknown(String),
+}
+
+fn color_error<E: Error>(err: &ColorError) -> E {
+    match err {
+        ColorError::Unknown(name) => E::unknown_variant(name, &["red", "green"]),
+    }
+}
+
+#[validate_deser(map_err = color_error)]
+#[derive(Debug)]
+struct Color(String);
+
+impl Validate for Color {
+    type Error = ColorError;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self.0.as_str() {
+            "red" | "green" => Ok(()),
+            other => Err(ColorError::Unknown(other.to_string())),
+        }
+    }
+}
+
+#[test]
+fn test_custom_mapper() {
+    assert!(serde_json::from_str::<Color>(r#""red""#).is_ok());
+    let err = serde_json::from_str::<Color>(r#""blue""#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown variant `blue`, expected `red` or `green`"
+    );
+}
+
+#[test]
+fn test_custom_mapper_keeps_typed_error() {
+    let result =
+        from_deserializer::<Color, _>(&mut serde_json::Deserializer::from_str(r#""blue""#));
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Validate(ColorError::Unknown(ref name))) if name == "blue"
+    ));
+}
+
+fn odd<E: Error>(value: &i32) -> E {
+    E::invalid_value(Unexpected::Signed((*value).into()), &"an even number")
+}
+
+#[validate_deser(map_err = "odd")]
+struct Even(i32);
+
+impl Validate for Even {
+    type Error = i32;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.0 % 2 == 0 {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+#[test]
+fn test_mapper_without_display_error() {
+    let err = serde_json::from_str::<Even>("3").err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value: integer `3`, expected an even number"
+    );
+}
+
+#[validate_deser(map_err = serde_validate::de::validation_errors)]
+#[derive(Debug, Validate)]
+struct Percentage {
+    #[validate(range(min = 0, max = 100))]
+    value: i32,
+    #[validate(length(min = 1))]
+    unit: String,
+}
+
+#[test]
+fn test_validation_errors_mapper() {
+    let err = serde_json::from_str::<Percentage>(r#"{ "value": 150, "unit": "%" }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value: 150, expected value: must be between 0 and 100"
+    );
+
+    let err = serde_json::from_str::<Percentage>(r#"{ "value": 50, "unit": "" }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid length 0, expected unit: length must be at least 1"
+    );
+
+    let err = serde_json::from_str::<Percentage>(r#"{ "value": -1, "unit": "" }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "value: must be between 0 and 100; unit: length must be at least 1"
+    );
+}
+
+fn not_negative(value: &i32) -> Result<(), ValidationError> {
+    serde_validate::rules::range(value, Some(&0), None)
+}
+
+#[validate_deser(map_err = serde_validate::de::validation_errors)]
+#[derive(Debug, Validate)]
+struct Stock {
+    #[validate(field = not_negative)]
+    count: i32,
+}
+
+#[test]
+fn test_field_check_uses_mapper() {
+    let err = serde_json::from_str::<Stock>(r#"{ "count": -1 }"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value: -1, expected count: must be at least 0 at line 1 column 15"
+    );
+}
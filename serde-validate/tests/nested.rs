@@ -0,0 +1,103 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_validate::{validate_deser, Validate, ValidationErrors};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Validate)]
+struct Item {
+    #[validate(length(min = 1))]
+    sku: String,
+    #[validate(range(min = 1))]
+    quantity: u32,
+}
+
+#[validate_deser]
+#[derive(Validate)]
+struct Order {
+    #[validate(nested)]
+    items: Vec<Item>,
+    #[validate(nested)]
+    gift: Option<Item>,
+    #[validate(nested)]
+    by_warehouse: BTreeMap<String, Box<Item>>,
+    #[validate(nested)]
+    pair: (Item, [Item; 1]),
+}
+
+fn item(sku: &str, quantity: u32) -> Item {
+    Item {
+        sku: sku.into(),
+        quantity,
+    }
+}
+
+fn paths(errors: &ValidationErrors) -> Vec<String> {
+    errors.iter().map(|e| e.path().to_string()).collect()
+}
+
+#[test]
+fn test_vec() {
+    let errors = vec![item("a", 1), item("", 0)].validate().unwrap_err();
+    assert_eq!(paths(&errors), vec!["[1].sku", "[1].quantity"]);
+}
+
+#[test]
+fn test_option() {
+    assert!(None::<Item>.validate().is_ok());
+    assert!(Some(item("", 1)).validate().is_err());
+}
+
+#[test]
+fn test_nested_fields() {
+    let order = Order {
+        items: vec![item("a", 1), item("b", 0)],
+        gift: Some(item("", 1)),
+        by_warehouse: BTreeMap::from([("north".to_string(), Box::new(item("c", 0)))]),
+        pair: (item("", 1), [item("d", 0)]),
+    };
+    let errors = order.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        vec![
+            "items[1].quantity",
+            "gift.sku",
+            "by_warehouse[\"north\"].quantity",
+            "pair[0].sku",
+            "pair[1][0].quantity",
+        ]
+    );
+}
+
+#[test]
+fn test_nested_deserialize() {
+    let valid = r#"{
+        "items": [{ "sku": "a", "quantity": 1 }],
+        "gift": null,
+        "by_warehouse": { "north": { "sku": "b", "quantity": 2 } },
+        "pair": [{ "sku": "c", "quantity": 1 }, [{ "sku": "d", "quantity": 1 }]]
+    }"#;
+    assert!(serde_json::from_str::<Order>(valid).is_ok());
+    let invalid = valid.replace("\"quantity\": 2", "\"quantity\": 0");
+    let err = serde_json::from_str::<Order>(&invalid).err().unwrap();
+    assert!(err
+        .to_string()
+        .starts_with("by_warehouse[\"north\"].quantity: must be at least 1"));
+}
@@ -0,0 +1,368 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::value::MapDeserializer;
+use serde_validate::de::{deserialize_unvalidated, Warnings};
+use serde_validate::{
+    from_deserializer_with_warnings, validate_deser, RepairAction, Validate, Validated,
+    ValidationError,
+};
+use std::collections::BTreeMap;
+
+fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        Err(ValidationError::new("blank").with_message("must not be blank"))
+    } else {
+        Ok(())
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Server {
+    #[validate(field = not_blank)]
+    host: String,
+    #[validate(range(min = 1))]
+    port: u16,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Config {
+    #[validate(length(min = 1), on_invalid = "default")]
+    name: Option<String>,
+    #[validate(range(min = 1, max = 10), on_invalid = "clamp")]
+    retries: u32,
+    #[validate(range(min = 0.0, max = 1.0), on_invalid = "clamp")]
+    ratio: Option<f64>,
+    #[validate(nested, on_invalid = "skip")]
+    servers: Vec<Server>,
+    #[validate(nested, on_invalid = "skip")]
+    #[serde(default)]
+    mirrors: BTreeMap<String, Server>,
+    #[validate(nested, on_invalid = "default")]
+    #[serde(default)]
+    backup: Option<Server>,
+}
+
+fn from_str(input: &str) -> (Config, Warnings) {
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    from_deserializer_with_warnings::<Config, _>(&mut deserializer).unwrap()
+}
+
+#[test]
+fn test_valid_input_has_no_repairs() {
+    let (config, repairs) = from_str(
+        r#"{ "name": "main", "retries": 3, "ratio": 0.5, "servers": [{ "host": "a", "port": 80 }] }"#,
+    );
+    assert_eq!(config.name.as_deref(), Some("main"));
+    assert_eq!(config.retries, 3);
+    assert_eq!(config.servers.len(), 1);
+    assert!(repairs.is_empty());
+}
+
+#[test]
+fn test_default_replaces_invalid_value() {
+    let (config, repairs) = from_str(r#"{ "name": "", "retries": 3, "servers": [] }"#);
+    assert_eq!(config.name, None);
+    assert_eq!(repairs.len(), 1);
+    assert_eq!(repairs[0].path().to_string(), "name");
+    assert_eq!(repairs[0].code(), "repaired");
+    assert_eq!(
+        repairs[0].params()["action"],
+        RepairAction::Default.policy()
+    );
+    assert_eq!(
+        repairs[0].message(),
+        Some("replaced by its default (length must be at least 1)")
+    );
+}
+
+#[test]
+fn test_clamp_into_range() {
+    let (config, repairs) = from_str(r#"{ "retries": 0, "ratio": 1.5, "servers": [] }"#);
+    assert_eq!(config.retries, 1);
+    assert_eq!(config.ratio, Some(1.0));
+    let repairs: Vec<_> = repairs.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        repairs,
+        [
+            "retries: clamped (must be between 1 and 10)",
+            "ratio: clamped (must be between 0 and 1)",
+        ]
+    );
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Ratios {
+    #[validate(range(min = 0.0, max = 1.0), on_invalid = "clamp")]
+    bounded: f64,
+    #[validate(range(max = 1.0), on_invalid = "clamp")]
+    capped: f64,
+}
+
+#[test]
+fn test_clamp_nan() {
+    let input = [("bounded", f64::NAN), ("capped", f64::NAN)];
+    let deserializer = MapDeserializer::<_, serde::de::value::Error>::new(input.into_iter());
+    let (ratios, repairs) = from_deserializer_with_warnings::<Ratios, _>(deserializer).unwrap();
+    assert_eq!(ratios.bounded, 0.0);
+    assert_eq!(ratios.capped, 1.0);
+    assert_eq!(repairs.len(), 2);
+    assert!(repairs
+        .iter()
+        .all(|r| r.params()["action"] == RepairAction::Clamp.policy()));
+}
+
+#[test]
+fn test_skip_drops_invalid_elements() {
+    let (config, repairs) = from_str(
+        r#"{
+            "retries": 3,
+            "servers": [{ "host": "a", "port": 0 }, { "host": "b", "port": 80 }, { "host": " ", "port": 80 }],
+            "mirrors": { "eu": { "host": "c", "port": 0 }, "us": { "host": "d", "port": 80 } }
+        }"#,
+    );
+    assert_eq!(config.servers.len(), 1);
+    assert_eq!(config.servers[0].host, "b");
+    assert_eq!(config.mirrors.keys().collect::<Vec<_>>(), ["us"]);
+    let repairs: Vec<_> = repairs.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        repairs,
+        [
+            "servers[0]: skipped (port: must be at least 1)",
+            "servers[2]: skipped (host: must not be blank)",
+            "mirrors[\"eu\"]: skipped (port: must be at least 1)",
+        ]
+    );
+}
+
+#[test]
+fn test_default_replaces_invalid_nested_value() {
+    let (config, repairs) =
+        from_str(r#"{ "retries": 3, "servers": [], "backup": { "host": "", "port": 80 } }"#);
+    assert!(config.backup.is_none());
+    assert_eq!(repairs.len(), 1);
+    assert_eq!(
+        repairs[0].to_string(),
+        "backup: replaced by its default (host: must not be blank)"
+    );
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Root {
+    #[validate(nested)]
+    config: Config,
+}
+
+#[test]
+fn test_nested_repairs_are_located_from_root() {
+    let mut deserializer = serde_json::Deserializer::from_str(
+        r#"{ "config": { "retries": 30, "servers": [{ "host": "a", "port": 0 }] } }"#,
+    );
+    let (root, repairs) = from_deserializer_with_warnings::<Root, _>(&mut deserializer).unwrap();
+    assert_eq!(root.config.retries, 10);
+    let paths: Vec<_> = repairs.iter().map(|r| r.path().to_string()).collect();
+    assert_eq!(paths, ["config.retries", "config.servers[0]"]);
+}
+
+#[test]
+fn test_repairs_apply_without_report() {
+    let config: Config = serde_json::from_str(
+        r#"{ "name": "", "retries": 11, "servers": [{ "host": "", "port": 1 }] }"#,
+    )
+    .unwrap();
+    assert_eq!(config.name, None);
+    assert_eq!(config.retries, 10);
+    assert!(config.servers.is_empty());
+}
+
+#[test]
+fn test_unrepaired_rules_still_fail() {
+    let err = serde_json::from_str::<Config>(r#"{ "retries": 3, "servers": 1 }"#).unwrap_err();
+    assert!(err.to_string().starts_with("invalid type"));
+}
+
+#[test]
+fn test_trusted_input_is_not_repaired() {
+    let mut deserializer = serde_json::Deserializer::from_str(
+        r#"{ "name": "", "retries": 30, "servers": [{ "host": "", "port": 0 }] }"#,
+    );
+    let config: Config = deserialize_unvalidated(&mut deserializer).unwrap();
+    assert_eq!(config.name.as_deref(), Some(""));
+    assert_eq!(config.retries, 30);
+    assert_eq!(config.servers.len(), 1);
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Leaf {
+    #[validate(range(min = 1))]
+    x: i32,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Mid {
+    #[validate(length(min = 1))]
+    name: String,
+    #[validate(range(max = 9), on_invalid = "clamp")]
+    #[serde(default)]
+    weight: u32,
+    leaf: Leaf,
+    #[serde(default)]
+    pinned: Option<Validated<Leaf>>,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Top {
+    #[validate(nested, on_invalid = "skip")]
+    mids: Vec<Mid>,
+    #[validate(nested, on_invalid = "default")]
+    #[serde(default)]
+    spare: Option<Mid>,
+}
+
+fn top_from_str(input: &str) -> Result<(Top, Warnings), String> {
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    from_deserializer_with_warnings::<Top, _>(&mut deserializer).map_err(|err| err.to_string())
+}
+
+#[test]
+fn test_skip_drops_elements_on_their_own_rules() {
+    let (top, repairs) = top_from_str(
+        r#"{ "mids": [
+            { "name": "", "weight": 20, "leaf": { "x": 1 } },
+            { "name": "b", "weight": 20, "leaf": { "x": 2 } }
+        ] }"#,
+    )
+    .unwrap();
+    assert_eq!(top.mids.len(), 1);
+    assert_eq!(top.mids[0].weight, 9);
+    assert_eq!(top.mids[0].leaf.x, 2);
+    assert!(top.mids[0].pinned.is_none());
+    let repairs: Vec<_> = repairs.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        repairs,
+        [
+            "mids[0]: skipped (name: length must be at least 1)",
+            "mids[1].weight: clamped (must be at most 9)",
+        ]
+    );
+}
+
+#[test]
+fn test_skip_checks_values_below_elements() {
+    let err = top_from_str(r#"{ "mids": [{ "name": "a", "leaf": { "x": 0 } }] }"#).unwrap_err();
+    assert!(err.starts_with("x: must be at least 1"));
+    let err =
+        top_from_str(r#"{ "mids": [{ "name": "a", "leaf": { "x": 1 }, "pinned": { "x": 0 } }] }"#)
+            .unwrap_err();
+    assert!(err.starts_with("x: must be at least 1"));
+}
+
+#[test]
+fn test_default_checks_values_below_value() {
+    let (top, repairs) =
+        top_from_str(r#"{ "mids": [], "spare": { "name": "", "leaf": { "x": 1 } } }"#).unwrap();
+    assert!(top.spare.is_none());
+    assert_eq!(repairs.len(), 1);
+    let err = top_from_str(r#"{ "mids": [], "spare": { "name": "a", "leaf": { "x": 0 } } }"#)
+        .unwrap_err();
+    assert!(err.starts_with("x: must be at least 1"));
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Port {
+    #[validate(range(min = 1), on_invalid = "default")]
+    number: u16,
+    #[validate(range(max = 8), on_invalid = "default")]
+    #[serde(default)]
+    threads: u32,
+}
+
+#[test]
+fn test_default_breaking_the_rules_fails() {
+    let port: Port = serde_json::from_str(r#"{ "number": 80, "threads": 9 }"#).unwrap();
+    assert_eq!(port.threads, 0);
+    let err = serde_json::from_str::<Port>(r#"{ "number": 0 }"#).unwrap_err();
+    assert!(
+        err.to_string().starts_with("number: must be at least 1"),
+        "{err}"
+    );
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Wrapped {
+    #[validate(nested, on_invalid = "skip")]
+    optional: Vec<Option<Leaf>>,
+    #[validate(nested, on_invalid = "skip")]
+    #[serde(default)]
+    boxed: BTreeMap<String, Box<Leaf>>,
+}
+
+#[test]
+fn test_skip_drops_wrapped_elements() {
+    let mut deserializer = serde_json::Deserializer::from_str(
+        r#"{ "optional": [{ "x": 0 }, null, { "x": 1 }], "boxed": { "a": { "x": 2 }, "b": { "x": -1 } } }"#,
+    );
+    let (wrapped, repairs) =
+        from_deserializer_with_warnings::<Wrapped, _>(&mut deserializer).unwrap();
+    assert_eq!(wrapped.optional.len(), 2);
+    assert!(wrapped.optional[0].is_none());
+    assert_eq!(wrapped.optional[1].as_ref().map(|leaf| leaf.x), Some(1));
+    assert_eq!(wrapped.boxed.len(), 1);
+    assert_eq!(wrapped.boxed["a"].x, 2);
+    let repairs: Vec<_> = repairs.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        repairs,
+        [
+            "optional[0]: skipped (x: must be at least 1)",
+            "boxed[\"b\"]: skipped (x: must be at least 1)",
+        ]
+    );
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Pool {
+    #[validate(range(min = 1, max = 16), on_invalid = "clamp")]
+    #[validate(range(max = 8), warn)]
+    size: u32,
+}
+
+#[test]
+fn test_repairs_precede_value_warnings() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "size": 20 }"#);
+    let (pool, warnings) = from_deserializer_with_warnings::<Pool, _>(&mut deserializer).unwrap();
+    assert_eq!(pool.size, 16);
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "size: clamped (must be between 1 and 16)",
+            "size: must be at most 8",
+        ]
+    );
+}
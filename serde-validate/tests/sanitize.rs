@@ -0,0 +1,126 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_validate::{validate_deser, Sanitize, Validate, ValidationError};
+use std::collections::HashMap;
+
+fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        Err(ValidationError::new("blank"))
+    } else {
+        Ok(())
+    }
+}
+
+fn strip_plus(value: &mut String) {
+    value.retain(|c| c != '+');
+}
+
+#[derive(Debug, PartialEq, Deserialize, Sanitize)]
+struct Tag(#[sanitizer(trim, uppercase)] String);
+
+#[validate_deser]
+#[derive(Debug, Validate, Sanitize)]
+struct Signup {
+    #[sanitizer(trim, lowercase)]
+    #[validate(length(max = 14))]
+    email: String,
+    #[sanitizer(trim, collapse_whitespace)]
+    #[validate(field = not_blank)]
+    name: String,
+    #[sanitizer(custom = strip_plus)]
+    phone: Option<String>,
+    #[sanitizer(nested)]
+    tags: Vec<Tag>,
+    #[sanitizer(nested)]
+    labels: HashMap<String, Tag>,
+}
+
+#[test]
+fn test_validate_deser_sanitizes_before_validating() {
+    let signup: Signup = serde_json::from_str(
+        r#"{
+            "email": "  Ann@EXAMPLE.io ",
+            "name": " Ann   Lee ",
+            "phone": "+34 600",
+            "tags": [" a "],
+            "labels": { "k": "v " }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(signup.email, "ann@example.io");
+    assert_eq!(signup.name, "Ann Lee");
+    assert_eq!(signup.phone.as_deref(), Some("34 600"));
+    assert_eq!(signup.tags, [Tag("A".into())]);
+    assert_eq!(signup.labels["k"], Tag("V".into()));
+}
+
+#[test]
+fn test_field_checks_see_sanitized_values() {
+    let err = serde_json::from_str::<Signup>(
+        r#"{ "email": "a@b.c", "name": "   ", "phone": null, "tags": [], "labels": {} }"#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("name: blank"));
+}
+
+#[test]
+fn test_derive_sanitize() {
+    let mut signup = Signup {
+        email: " A@B.C ".into(),
+        name: "a\t b".into(),
+        phone: None,
+        tags: vec![Tag(" x".into())],
+        labels: HashMap::new(),
+    };
+    signup.sanitize();
+    assert_eq!(signup.email, "a@b.c");
+    assert_eq!(signup.name, "a b");
+    assert_eq!(signup.tags, [Tag("X".into())]);
+}
+
+#[validate_deser]
+#[derive(Debug)]
+enum Contact {
+    Email(#[sanitizer(trim, lowercase)] String),
+    Phone {
+        #[sanitizer(custom = strip_plus)]
+        number: String,
+    },
+}
+
+impl Validate for Contact {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Contact::Email(email) if email.contains(' ') => Err("spaces".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_validate_deser_sanitizes_enums_without_derive() {
+    let contact: Contact = serde_json::from_str(r#"{ "Email": " A@B.C " }"#).unwrap();
+    assert!(matches!(contact, Contact::Email(ref email) if email == "a@b.c"));
+    let contact: Contact = serde_json::from_str(r#"{ "Phone": { "number": "+1" } }"#).unwrap();
+    assert!(matches!(contact, Contact::Phone { ref number } if number == "1"));
+}
@@ -0,0 +1,91 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{validate_ser, validate_serde, Validate};
+
+#[validate_ser]
+#[derive(Validate)]
+struct Outbound {
+    #[validate(length(min = 1))]
+    name: String,
+    count: u32,
+}
+
+#[validate_serde]
+#[derive(Debug, Validate)]
+enum Event<T>
+where
+    T: Validate<Error = serde_validate::ValidationErrors>,
+{
+    Created {
+        #[validate(range(min = 1))]
+        id: u64,
+    },
+    Payload(#[validate(nested)] T),
+    Closed,
+}
+
+#[validate_serde]
+#[derive(Debug, Validate)]
+struct Ratio(#[validate(range(min = 0.0, max = 1.0))] f64);
+
+#[test]
+fn test_serialize_ok() {
+    let outbound = Outbound {
+        name: "a".into(),
+        count: 1,
+    };
+    assert_eq!(
+        serde_json::to_string(&outbound).unwrap(),
+        r#"{"name":"a","count":1}"#
+    );
+}
+
+#[test]
+fn test_serialize_invalid() {
+    let outbound = Outbound {
+        name: String::new(),
+        count: 1,
+    };
+    let err = serde_json::to_string(&outbound).unwrap_err();
+    assert_eq!(err.to_string(), "name: length must be at least 1");
+}
+
+#[test]
+fn test_serde_roundtrip() {
+    let ratio: Ratio = serde_json::from_str("0.5").unwrap();
+    assert_eq!(serde_json::to_string(&ratio).unwrap(), "0.5");
+    assert!(serde_json::from_str::<Ratio>("1.5").is_err());
+    assert!(serde_json::to_string(&Ratio(1.5)).is_err());
+}
+
+#[test]
+fn test_serde_generic_enum() {
+    let event: Event<Ratio> = serde_json::from_str(r#"{ "Payload": 0.25 }"#).unwrap();
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"Payload":0.25}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&Event::<Ratio>::Closed).unwrap(),
+        r#""Closed""#
+    );
+    assert!(serde_json::to_string(&Event::<Ratio>::Created { id: 0 }).is_err());
+    assert!(serde_json::to_string(&Event::Payload(Ratio(2.0))).is_err());
+}
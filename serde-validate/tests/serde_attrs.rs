@@ -0,0 +1,290 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+use serde_validate::{validate_deser, validate_serde, Validate};
+
+fn non_negative(value: i32) -> Result<(), String> {
+    if value >= 0 {
+        Ok(())
+    } else {
+        Err("must not be negative".to_string())
+    }
+}
+
+#[validate_deser]
+#[derive(Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Account {
+    user_name: String,
+    #[serde(rename = "years")]
+    age_in_years: i32,
+}
+
+impl Validate for Account {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        non_negative(self.age_in_years)
+    }
+}
+
+#[test]
+fn test_rename_all() {
+    let account: Account = serde_json::from_str(r#"{ "userName": "lucas", "years": 30 }"#).unwrap();
+    assert_eq!(account.user_name, "lucas");
+    assert_eq!(account.age_in_years, 30);
+}
+
+#[test]
+fn test_rename_all_still_validates() {
+    let err =
+        serde_json::from_str::<Account>(r#"{ "userName": "lucas", "years": -1 }"#).unwrap_err();
+    assert_eq!(err.to_string(), "must not be negative");
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    assert!(serde_json::from_str::<Account>(
+        r#"{ "userName": "lucas", "years": 30, "admin": true }"#
+    )
+    .is_err());
+}
+
+#[validate_deser]
+#[derive(Debug, Default, PartialEq)]
+#[serde(default)]
+struct Settings {
+    retries: i32,
+    verbose: bool,
+}
+
+impl Validate for Settings {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        non_negative(self.retries)
+    }
+}
+
+#[test]
+fn test_container_default() {
+    let settings: Settings = serde_json::from_str(r#"{ "verbose": true }"#).unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            retries: 0,
+            verbose: true
+        }
+    );
+}
+
+fn default_limits() -> Limits {
+    Limits(-1, 10)
+}
+
+#[validate_deser]
+#[serde(default = "default_limits")]
+struct Limits(i32, i32);
+
+impl Validate for Limits {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        non_negative(self.0)
+    }
+}
+
+#[test]
+fn test_container_default_path() {
+    let limits: Limits = serde_json::from_str("[1, 2]").unwrap();
+    assert_eq!((limits.0, limits.1), (1, 2));
+}
+
+#[validate_deser]
+#[derive(Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Internal {
+    Point {
+        x: i32,
+        y: i32,
+    },
+    #[serde(rename = "nothing")]
+    Empty,
+}
+
+impl Validate for Internal {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Internal::Point { x, y } => non_negative(*x).and(non_negative(*y)),
+            Internal::Empty => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_internally_tagged() {
+    assert_eq!(
+        serde_json::from_str::<Internal>(r#"{ "type": "point", "x": 1, "y": 2 }"#).unwrap(),
+        Internal::Point { x: 1, y: 2 }
+    );
+    assert_eq!(
+        serde_json::from_str::<Internal>(r#"{ "type": "nothing" }"#).unwrap(),
+        Internal::Empty
+    );
+    assert!(serde_json::from_str::<Internal>(r#"{ "type": "point", "x": -1, "y": 2 }"#).is_err());
+    assert!(serde_json::from_str::<Internal>(r#"{ "Point": { "x": 1, "y": 2 } }"#).is_err());
+}
+
+#[validate_deser]
+#[derive(Debug, PartialEq)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Count(i32),
+    Name(String),
+}
+
+impl Validate for Adjacent {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Adjacent::Count(count) => non_negative(*count),
+            Adjacent::Name(_) => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    assert_eq!(
+        serde_json::from_str::<Adjacent>(r#"{ "t": "Count", "c": 3 }"#).unwrap(),
+        Adjacent::Count(3)
+    );
+    assert_eq!(
+        serde_json::from_str::<Adjacent>(r#"{ "t": "Name", "c": "x" }"#).unwrap(),
+        Adjacent::Name("x".to_string())
+    );
+    assert!(serde_json::from_str::<Adjacent>(r#"{ "t": "Count", "c": -3 }"#).is_err());
+}
+
+#[validate_deser]
+#[derive(Debug, PartialEq)]
+#[serde(untagged)]
+enum Untagged {
+    Count(i32),
+    Name(String),
+}
+
+impl Validate for Untagged {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Untagged::Count(count) => non_negative(*count),
+            Untagged::Name(_) => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_untagged() {
+    assert_eq!(
+        serde_json::from_str::<Untagged>("3").unwrap(),
+        Untagged::Count(3)
+    );
+    assert_eq!(
+        serde_json::from_str::<Untagged>(r#""x""#).unwrap(),
+        Untagged::Name("x".to_string())
+    );
+    let err = serde_json::from_str::<Untagged>("-3").unwrap_err();
+    assert_eq!(err.to_string(), "must not be negative");
+}
+
+#[validate_deser]
+#[derive(Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct Shared {
+    value: i32,
+}
+
+impl Validate for Shared {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        non_negative(self.value)
+    }
+}
+
+#[test]
+fn test_attributes_kept_for_other_serde_derives() {
+    let shared: Shared = serde_json::from_str(r#"{ "VALUE": 1 }"#).unwrap();
+    assert_eq!(serde_json::to_string(&shared).unwrap(), r#"{"VALUE":1}"#);
+}
+
+#[validate_serde]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Shape {
+    Circle { radius: i32 },
+    Square { side: i32 },
+}
+
+impl Validate for Shape {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Shape::Circle { radius } => non_negative(*radius),
+            Shape::Square { side } => non_negative(*side),
+        }
+    }
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let json = r#"{"kind":"circle","radius":2}"#;
+    let shape: Shape = serde_json::from_str(json).unwrap();
+    assert_eq!(serde_json::to_string(&shape).unwrap(), json);
+    assert!(serde_json::to_string(&Shape::Square { side: -1 }).is_err());
+}
+
+#[validate_deser]
+#[derive(Default)]
+#[serde(default, rename_all = "UPPERCASE")]
+struct Wrapper<T> {
+    inner: T,
+    count: i32,
+}
+
+impl<T> Validate for Wrapper<T> {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        non_negative(self.count)
+    }
+}
+
+#[test]
+fn test_generic_container_default() {
+    let wrapper: Wrapper<String> = serde_json::from_str(r#"{ "INNER": "x" }"#).unwrap();
+    assert_eq!((wrapper.inner.as_str(), wrapper.count), ("x", 0));
+    assert!(serde_json::from_str::<Wrapper<String>>(r#"{ "COUNT": -1 }"#).is_err());
+}
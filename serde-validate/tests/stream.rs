@@ -0,0 +1,234 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_validate::{
+    from_deserializer, validate_deser, DeserializeError, Validate, ValidatedMap, ValidatedSeq,
+    ValidationError, ValidationErrors,
+};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+thread_local! {
+    static DESERIALIZED: Cell<usize> = const { Cell::new(0) };
+    static VALIDATED: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Serialize)]
+struct Item(i32);
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DESERIALIZED.with(|count| count.set(count.get() + 1));
+        i32::deserialize(deserializer).map(Item)
+    }
+}
+
+impl Validate for Item {
+    type Error = ValidationError;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.0 >= 0 {
+            Ok(())
+        } else {
+            Err(ValidationError::new("negative").with_message("must not be negative"))
+        }
+    }
+}
+
+fn positive(value: &i32) -> Result<(), ValidationError> {
+    if *value > 0 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+#[validate_deser]
+#[derive(Debug)]
+struct Order {
+    #[validate(field = positive)]
+    quantity: i32,
+    price: i32,
+}
+
+impl Validate for Order {
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        VALIDATED.with(|count| count.set(count.get() + 1));
+        let mut errors = ValidationErrors::new();
+        if self.price < 0 {
+            errors.push(ValidationError::new("negative").at("price"));
+        }
+        errors.into_result()
+    }
+}
+
+#[test]
+fn test_seq_valid() {
+    let items: ValidatedSeq<Item> = serde_json::from_str("[1, 2, 3]").unwrap();
+    assert_eq!(
+        items.iter().map(|item| item.0).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert!(items.validate().is_ok());
+}
+
+#[test]
+fn test_seq_stops_at_first_invalid_element() {
+    DESERIALIZED.with(|count| count.set(0));
+    let err = serde_json::from_str::<ValidatedSeq<Item>>("[1, -2, 3, -4]")
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "[1]: must not be negative at line 1 column 8"
+    );
+    assert_eq!(DESERIALIZED.with(Cell::get), 2);
+}
+
+#[test]
+fn test_seq_stops_before_malformed_rest() {
+    let err = serde_json::from_str::<ValidatedSeq<Item>>(r#"[-1, "not a number""#)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "[0]: must not be negative at line 1 column 5"
+    );
+}
+
+#[test]
+fn test_seq_keeps_typed_error() {
+    let mut deserializer = serde_json::Deserializer::from_str("[0, 1, -1]");
+    match from_deserializer::<ValidatedSeq<Item>, _>(&mut deserializer) {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.at_path("[2]").count(), 1);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_seq_new_validates() {
+    assert!(ValidatedSeq::new(vec![Item(1)]).is_ok());
+    let errors = ValidatedSeq::new(vec![Item(-1), Item(1), Item(-1)]).unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn test_seq_serializes_as_sequence() {
+    let items = ValidatedSeq::new(vec![Item(1), Item(2)]).unwrap();
+    assert_eq!(serde_json::to_string(&items).unwrap(), "[1,2]");
+}
+
+#[test]
+fn test_map_valid() {
+    let map: ValidatedMap<String, Item> = serde_json::from_str(r#"{ "a": 1, "b": 2 }"#).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["b"].0, 2);
+}
+
+#[test]
+fn test_map_stops_at_first_invalid_value() {
+    DESERIALIZED.with(|count| count.set(0));
+    let err = serde_json::from_str::<ValidatedMap<String, Item>>(r#"{ "a": -1, "b": 2 }"#)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "[\"a\"]: must not be negative at line 1 column 9"
+    );
+    assert_eq!(DESERIALIZED.with(Cell::get), 1);
+}
+
+#[test]
+fn test_map_keeps_typed_error() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{ "x": -1 }"#);
+    match from_deserializer::<ValidatedMap<String, Item>, _>(&mut deserializer) {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.at_path("[\"x\"]").count(), 1);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_map_new_validates() {
+    let map = HashMap::from([("a".to_string(), Item(-1))]);
+    assert!(ValidatedMap::new(map).is_err());
+}
+
+#[test]
+fn test_seq_validates_validate_deser_elements_once() {
+    VALIDATED.with(|count| count.set(0));
+    let orders: ValidatedSeq<Order> =
+        serde_json::from_str(r#"[{ "quantity": 1, "price": 2 }, { "quantity": 3, "price": 4 }]"#)
+            .unwrap();
+    assert_eq!(
+        orders
+            .iter()
+            .map(|order| order.quantity)
+            .collect::<Vec<_>>(),
+        [1, 3]
+    );
+    assert_eq!(VALIDATED.with(Cell::get), 2);
+}
+
+#[test]
+fn test_seq_reports_validate_deser_element_errors_at_index() {
+    let err = serde_json::from_str::<ValidatedSeq<Order>>(
+        r#"[{ "quantity": 1, "price": 2 }, { "quantity": 1, "price": -2 }]"#,
+    )
+    .err()
+    .unwrap();
+    assert!(err.to_string().starts_with("[1].price: negative"), "{err}");
+
+    let err = serde_json::from_str::<ValidatedSeq<Order>>(
+        r#"[{ "quantity": 1, "price": 2 }, { "quantity": 0, "price": 2 }]"#,
+    )
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string().starts_with("[1].quantity: positive"),
+        "{err}"
+    );
+
+    let mut deserializer = serde_json::Deserializer::from_str(r#"[{ "quantity": 0, "price": 2 }]"#);
+    match from_deserializer::<ValidatedSeq<Order>, _>(&mut deserializer) {
+        Err(DeserializeError::Validate(errors)) => {
+            assert_eq!(errors.at_path("[0].quantity").count(), 1);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_map_reports_validate_deser_value_errors_at_key() {
+    let err = serde_json::from_str::<ValidatedMap<String, Order>>(
+        r#"{ "a": { "quantity": 0, "price": 2 } }"#,
+    )
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string().starts_with("[\"a\"].quantity: positive"),
+        "{err}"
+    );
+}
@@ -0,0 +1,9 @@
+use serde_validate::Validate;
+
+#[derive(Validate)]
+struct Config {
+    #[validate(length(max = 10), on_invalid = "clamp")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use serde_validate::Validate;
+
+#[derive(Validate)]
+struct Company {
+    #[validate(required)]
+    vat_id: String,
+}
+
+fn main() {}
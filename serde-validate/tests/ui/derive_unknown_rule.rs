@@ -0,0 +1,9 @@
+use serde_validate::Validate;
+
+#[derive(Validate)]
+struct Person {
+    #[validate(lenght(max = 10))]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use serde_validate::Validate;
+
+#[derive(Validate)]
+struct Order {
+    #[validate(range(max = 100), warn, groups = ["create"])]
+    quantity: u32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use serde_validate::validate_deser;
+
+#[validate_deser]
+#[derive(Debug, Deserialize)]
+struct Positive(i32);
+
+fn main() {}
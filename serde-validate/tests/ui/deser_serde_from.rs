@@ -0,0 +1,7 @@
+use serde_validate::validate_deser;
+
+#[validate_deser]
+#[serde(from = "i32")]
+struct Positive(i32);
+
+fn main() {}
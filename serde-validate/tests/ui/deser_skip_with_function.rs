@@ -0,0 +1,15 @@
+use serde_validate::{validate_deser, Validate};
+
+fn deserialize_names<'de, D: serde::Deserializer<'de>>(_: D) -> Result<Vec<String>, D::Error> {
+    Ok(Vec::new())
+}
+
+#[validate_deser]
+#[derive(Validate)]
+struct Config {
+    #[validate(on_invalid = "skip")]
+    #[serde(deserialize_with = "deserialize_names")]
+    names: Vec<String>,
+}
+
+fn main() {}
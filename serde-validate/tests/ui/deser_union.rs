@@ -0,0 +1,9 @@
+use serde_validate::validate_deser;
+
+#[validate_deser]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
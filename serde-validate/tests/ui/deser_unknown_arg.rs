@@ -0,0 +1,6 @@
+use serde_validate::validate_deser;
+
+#[validate_deser(contxt = u32)]
+struct Positive(i32);
+
+fn main() {}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+use serde_validate::validate_ser;
+
+#[validate_ser]
+#[derive(Serialize)]
+struct Positive(i32);
+
+fn main() {}
@@ -0,0 +1,6 @@
+use serde_validate::validate_ser;
+
+#[validate_ser(context = u32)]
+struct Positive(i32);
+
+fn main() {}
@@ -0,0 +1,6 @@
+use serde_validate::validate_serde;
+
+#[validate_serde(context = u32)]
+struct Positive(i32);
+
+fn main() {}
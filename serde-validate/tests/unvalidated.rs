@@ -0,0 +1,159 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::de::DeserializeSeed;
+use serde::Deserialize;
+use serde_validate::de::{deserialize_unvalidated, Unchecked, WithContext};
+use serde_validate::{
+    validate_deser, Validate, ValidateWith, Validated, ValidatedSeq, ValidationError,
+};
+use std::cell::Cell;
+
+thread_local! {
+    static VALIDATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn validations() -> usize {
+    VALIDATIONS.with(|count| count.replace(0))
+}
+
+fn positive(value: &i32) -> Result<(), ValidationError> {
+    VALIDATIONS.with(|count| count.set(count.get() + 1));
+    if *value > 0 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Line {
+    #[validate(field = positive)]
+    quantity: i32,
+    #[validate(max_items = 1)]
+    notes: Vec<String>,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Snapshot {
+    #[validate(custom = positive)]
+    version: i32,
+    #[validate(nested)]
+    lines: Vec<Line>,
+}
+
+const INVALID: &str = r#"{ "version": 0, "lines": [{ "quantity": -1, "notes": [] }] }"#;
+
+#[test]
+fn test_validated_by_default() {
+    assert!(serde_json::from_str::<Snapshot>(INVALID).is_err());
+}
+
+#[test]
+fn test_deserialize_unvalidated_skips_all_checks() {
+    validations();
+    let mut deserializer = serde_json::Deserializer::from_str(INVALID);
+    let snapshot: Snapshot = deserialize_unvalidated(&mut deserializer).unwrap();
+    assert_eq!(snapshot.version, 0);
+    assert_eq!(snapshot.lines[0].quantity, -1);
+    assert_eq!(validations(), 0);
+}
+
+#[test]
+fn test_unchecked_wrapper() {
+    let Unchecked(snapshot) = serde_json::from_str::<Unchecked<Snapshot>>(INVALID).unwrap();
+    assert_eq!(snapshot.version, 0);
+    assert_eq!(validations(), 0);
+}
+
+#[test]
+fn test_validation_resumes_after_unchecked() {
+    serde_json::from_str::<Unchecked<Snapshot>>(INVALID).unwrap();
+    assert!(serde_json::from_str::<Snapshot>(INVALID).is_err());
+}
+
+#[test]
+fn test_unchecked_keeps_limits() {
+    let input = r#"{ "quantity": 1, "notes": ["a", "b"] }"#;
+    assert!(serde_json::from_str::<Unchecked<Line>>(input).is_err());
+}
+
+#[derive(Debug, Deserialize)]
+struct Wrapped {
+    line: Validated<Line>,
+    lines: ValidatedSeq<Line>,
+}
+
+#[test]
+fn test_unchecked_skips_wrappers() {
+    let input = r#"{
+        "line": { "quantity": 1, "notes": [] },
+        "lines": [{ "quantity": -2, "notes": [] }]
+    }"#;
+    assert!(serde_json::from_str::<Wrapped>(input).is_err());
+    let Unchecked(wrapped) = serde_json::from_str::<Unchecked<Wrapped>>(input).unwrap();
+    assert_eq!(wrapped.line.quantity, 1);
+    assert_eq!(wrapped.lines[0].quantity, -2);
+}
+
+#[test]
+fn test_unchecked_still_validates_validated() {
+    assert!(serde_json::from_str::<Unchecked<Validated<Snapshot>>>(INVALID).is_err());
+    let mut deserializer = serde_json::Deserializer::from_str(INVALID);
+    assert!(deserialize_unvalidated::<Validated<Snapshot>, _>(&mut deserializer).is_err());
+
+    let valid = r#"{ "version": 1, "lines": [{ "quantity": 1, "notes": [] }] }"#;
+    let Unchecked(snapshot) =
+        serde_json::from_str::<Unchecked<Validated<Snapshot>>>(valid).unwrap();
+    assert_eq!(snapshot.version, 1);
+}
+
+#[validate_deser(context = i32)]
+#[derive(Debug)]
+struct Limit(i32);
+
+impl ValidateWith<i32> for Limit {
+    type Error = String;
+
+    fn validate_with(&self, max: &i32) -> Result<(), Self::Error> {
+        if self.0 <= *max {
+            Ok(())
+        } else {
+            Err("over the limit".into())
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Trusted<T>(T);
+
+impl<'de, T: serde_validate::de::DeserializeWith<'de, i32>> Deserialize<'de> for Trusted<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        WithContext::new(&0).deserialize(deserializer).map(Trusted)
+    }
+}
+
+#[test]
+fn test_unchecked_with_context() {
+    assert!(serde_json::from_str::<Trusted<Limit>>("5").is_err());
+    let Unchecked(Trusted(limit)) = serde_json::from_str::<Unchecked<Trusted<Limit>>>("5").unwrap();
+    assert_eq!(limit.0, 5);
+}
@@ -0,0 +1,143 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_validate::{validate_deser, Validate, Validated, ValidationError};
+use std::cell::Cell;
+
+thread_local! {
+    static VALIDATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+struct Order {
+    #[validate(range(min = 1))]
+    quantity: u32,
+}
+
+fn total(order: &Validated<Order>) -> u32 {
+    order.quantity * 10
+}
+
+#[test]
+fn test_new() {
+    let order = Validated::new(Order { quantity: 2 }).unwrap();
+    assert_eq!(total(&order), 20);
+    assert!(Validated::new(Order { quantity: 0 }).is_err());
+}
+
+#[test]
+fn test_into_validated() {
+    let order = Order { quantity: 3 }.into_validated().unwrap();
+    assert_eq!(order.into_inner().quantity, 3);
+    assert!(Order { quantity: 0 }.into_validated().is_err());
+}
+
+#[test]
+fn test_deserialize() {
+    let order: Validated<Order> = serde_json::from_str(r#"{ "quantity": 1 }"#).unwrap();
+    assert_eq!(order.quantity, 1);
+    assert!(serde_json::from_str::<Validated<Order>>(r#"{ "quantity": 0 }"#).is_err());
+}
+
+#[test]
+fn test_serialize_transparent() {
+    let order = Validated::new(Order { quantity: 4 }).unwrap();
+    assert_eq!(serde_json::to_string(&order).unwrap(), r#"{"quantity":4}"#);
+}
+
+#[test]
+fn test_nested_in_plain_derive() {
+    #[derive(Deserialize)]
+    struct Cart {
+        orders: Vec<Validated<Order>>,
+    }
+    let cart: Cart = serde_json::from_str(r#"{ "orders": [{ "quantity": 1 }] }"#).unwrap();
+    assert_eq!(cart.orders.len(), 1);
+    assert!(serde_json::from_str::<Cart>(r#"{ "orders": [{ "quantity": 0 }] }"#).is_err());
+}
+
+#[derive(Debug, Clone, Validate)]
+struct Reservation {
+    #[validate(range(min = 1, max = 10))]
+    seats: u32,
+}
+
+#[test]
+fn test_modify_ok() {
+    let mut reservation = Validated::new(Reservation { seats: 2 }).unwrap();
+    reservation.modify(|r| r.seats += 3).unwrap();
+    assert_eq!(reservation.seats, 5);
+}
+
+#[test]
+fn test_modify_rolls_back() {
+    let mut reservation = Validated::new(Reservation { seats: 2 }).unwrap();
+    let errors = reservation.modify(|r| r.seats = 20).unwrap_err();
+    assert_eq!(errors.to_string(), "seats: must be between 1 and 10");
+    assert_eq!(reservation.seats, 2);
+}
+
+#[test]
+fn test_into_modified_without_clone() {
+    let order = Validated::new(Order { quantity: 2 }).unwrap();
+    let order = order.into_modified(|o| o.quantity += 1).unwrap();
+    assert_eq!(order.quantity, 3);
+    let (invalid, errors) = order.into_modified(|o| o.quantity = 0).unwrap_err();
+    assert_eq!(invalid.quantity, 0);
+    assert_eq!(errors.to_string(), "quantity: must be at least 1");
+}
+
+#[test]
+fn test_try_map() {
+    let reservation = Validated::new(Reservation { seats: 2 }).unwrap();
+    let doubled = reservation
+        .try_map(|r| Reservation { seats: r.seats * 2 })
+        .unwrap();
+    assert_eq!((reservation.seats, doubled.seats), (2, 4));
+    assert!(reservation.try_map(|_| Order { quantity: 0 }).is_err());
+}
+
+#[validate_deser]
+#[derive(Debug)]
+struct Counted(i32);
+
+impl Validate for Counted {
+    type Error = ValidationError;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        VALIDATIONS.with(|count| count.set(count.get() + 1));
+        if self.0 > 0 {
+            Ok(())
+        } else {
+            Err(ValidationError::new("positive"))
+        }
+    }
+}
+
+#[test]
+fn test_deserialize_validate_deser_once() {
+    VALIDATIONS.with(|count| count.set(0));
+    let counted: Validated<Counted> = serde_json::from_str("1").unwrap();
+    assert_eq!(counted.0, 1);
+    assert_eq!(VALIDATIONS.with(Cell::get), 1);
+
+    let err = serde_json::from_str::<Validated<Counted>>("0").unwrap_err();
+    assert!(err.to_string().starts_with("positive"), "{err}");
+}
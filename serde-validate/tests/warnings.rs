@@ -0,0 +1,195 @@
+/*
+ * serde-validate - A library for validating deserialized structs and enums
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_validate::{
+    from_deserializer_with_warnings, validate_deser, DeserializeError, Severity, Validate,
+    ValidationError, ValidationErrors, Warning,
+};
+
+fn deprecated(value: &str) -> Result<(), ValidationError> {
+    if value == "legacy" {
+        Err(ValidationError::new("deprecated").with_message("is deprecated"))
+    } else {
+        Ok(())
+    }
+}
+
+fn no_workers(config: &Config) -> Result<(), ValidationError> {
+    if config.workers.is_empty() {
+        Err(ValidationError::new("no_workers").with_message("has no workers"))
+    } else {
+        Ok(())
+    }
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+struct Worker {
+    #[validate(length(min = 1))]
+    #[validate(custom = deprecated, warn)]
+    kind: String,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+#[validate(custom = no_workers, warn)]
+struct Config {
+    #[validate(range(min = 1))]
+    #[validate(range(max = 64), warn)]
+    threads: u32,
+    #[validate(nested)]
+    workers: Vec<Worker>,
+    #[validate(nested)]
+    backup: Option<Worker>,
+}
+
+#[validate_deser]
+#[derive(Debug, Validate)]
+enum Source {
+    File(#[validate(custom = deprecated, warn)] String),
+    Inline {
+        #[validate(nested)]
+        config: Config,
+    },
+}
+
+fn from_str(input: &str) -> Vec<String> {
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    let (_, warnings) = from_deserializer_with_warnings::<Config, _>(&mut deserializer).unwrap();
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.severity() == Severity::Warning));
+    warnings.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_no_warnings() {
+    assert!(
+        from_str(r#"{ "threads": 4, "workers": [{ "kind": "web" }], "backup": null }"#).is_empty()
+    );
+}
+
+#[test]
+fn test_field_and_container_warnings() {
+    assert_eq!(
+        from_str(r#"{ "threads": 100, "workers": [], "backup": null }"#),
+        ["threads: must be at most 64", "has no workers"]
+    );
+}
+
+#[test]
+fn test_nested_warnings_are_located() {
+    assert_eq!(
+        from_str(
+            r#"{ "threads": 4, "workers": [{ "kind": "web" }, { "kind": "legacy" }], "backup": { "kind": "legacy" } }"#
+        ),
+        [
+            "workers[1].kind: is deprecated",
+            "backup.kind: is deprecated"
+        ]
+    );
+}
+
+#[test]
+fn test_warnings_do_not_fail_validation() {
+    let config = Config {
+        threads: 100,
+        workers: Vec::new(),
+        backup: None,
+    };
+    assert!(config.validate().is_ok());
+    assert_eq!(config.warnings().len(), 2);
+}
+
+#[test]
+fn test_errors_still_fail_deserialization() {
+    let mut deserializer =
+        serde_json::Deserializer::from_str(r#"{ "threads": 0, "workers": [], "backup": null }"#);
+    let result = from_deserializer_with_warnings::<Config, _>(&mut deserializer);
+    assert!(matches!(result, Err(DeserializeError::Validate(_))));
+}
+
+#[test]
+fn test_validate_report() {
+    let config = Config {
+        threads: 0,
+        workers: vec![Worker {
+            kind: "legacy".into(),
+        }],
+        backup: None,
+    };
+    let report = config.validate_report();
+    assert!(!report.is_valid());
+    assert_eq!(report.errors().len(), 1);
+    assert_eq!(report.warnings().len(), 1);
+    assert_eq!(
+        report.to_string(),
+        "threads: must be at least 1; warning: workers[0].kind: is deprecated"
+    );
+    let errors: ValidationErrors = report.into_result().unwrap_err();
+    assert_eq!(errors.iter().next().unwrap().severity(), Severity::Error);
+}
+
+#[test]
+fn test_enum_warnings() {
+    let source: Source = serde_json::from_str(r#"{ "File": "legacy" }"#).unwrap();
+    assert_eq!(source.warnings()[0].to_string(), "[0]: is deprecated");
+
+    let source: Source = serde_json::from_str(
+        r#"{ "Inline": { "config": { "threads": 100, "workers": [{ "kind": "web" }], "backup": null } } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        source.validate_report().into_result().unwrap()[0].to_string(),
+        "config.threads: must be at most 64"
+    );
+}
+
+struct Port(u16);
+
+impl Validate for Port {
+    type Error = ValidationErrors;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        if self.0 < 1024 {
+            vec![
+                Warning::new("privileged"),
+                ValidationError::new("well_known").into(),
+            ]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn test_hand_written_warnings_have_warning_severity() {
+    assert!(Port(8080).warnings().is_empty());
+    let report = Port(80).validate_report();
+    assert!(report.is_valid());
+    assert_eq!(report.warnings().len(), 2);
+    assert!(report
+        .warnings()
+        .iter()
+        .all(|warning| warning.severity() == Severity::Warning));
+}